@@ -0,0 +1,37 @@
+use connect_four_engine::Game;
+use rand::{rngs::SmallRng, SeedableRng};
+use std::time::Instant;
+
+const NUM_ROLLOUTS: u32 = 1_000_000;
+
+const STATS_DEPTH: u8 = 9;
+
+const BOLD: &str = "\x1b[1;37m";
+const RESET: &str = "\x1b[0m";
+
+/// Measures playing games to the end, where every move checks whether it won the game.
+fn main() {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let mut total_moves = 0u64;
+
+    let now = Instant::now();
+    for _ in 0..NUM_ROLLOUTS {
+        let mut game = Game::new();
+        while game.play_random(&mut rng).is_some() {}
+        total_moves += u64::from(game.num_moves());
+    }
+    let time = now.elapsed();
+    println!(
+        "{:>15} {BOLD}{time:>10.3?}{RESET} {total_moves:>12} moves",
+        "rollouts"
+    );
+
+    let now = Instant::now();
+    let stats = Game::terminal_stats(STATS_DEPTH);
+    let time = now.elapsed();
+    println!(
+        "{:>15} {BOLD}{time:>10.3?}{RESET} {:>12} positions",
+        "terminal stats",
+        stats.p1_wins + stats.p2_wins + stats.draws + stats.ongoing
+    );
+}
@@ -0,0 +1,79 @@
+use connect_four_engine::{Cache, ConcurrentCache, Engine, Game, TranspositionTable};
+use std::fs::File;
+use std::io::{prelude::*, BufReader};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+const TEST_DATA: &str = "begin_hard";
+
+const NUM_LINES: usize = 10;
+
+const BOLD: &str = "\x1b[1;37m";
+const RESET: &str = "\x1b[0m";
+
+/// Compares searching every move of each position with one thread per move,
+/// where each thread either has its own table or shares a concurrent table with the others.
+fn main() {
+    let games = read_games();
+
+    let (time, nodes) = bench(&games, Engine::<Cache>::default, |engine, game| {
+        engine.evaluate_next(game)
+    });
+    print_result("sequential", time, nodes);
+
+    let (time, nodes) = bench(&games, Engine::<Cache>::default, |engine, game| {
+        engine.evaluate_next_parallel(game)
+    });
+    print_result("parallel", time, nodes);
+
+    let (time, nodes) = bench(
+        &games,
+        Engine::<Arc<ConcurrentCache>>::default,
+        |engine, game| engine.evaluate_next_parallel(game),
+    );
+    print_result("parallel shared", time, nodes);
+}
+
+fn read_games() -> Vec<Game> {
+    let file = File::open(format!("./test_data/{TEST_DATA}.csv")).unwrap();
+    let reader = BufReader::new(file);
+
+    // Skip the header
+    reader
+        .lines()
+        .skip(1)
+        .take(NUM_LINES)
+        .map(|line| {
+            let line = line.unwrap();
+            let (moves, _) = line
+                .split_once(',')
+                .expect("file line should have moves and score separated by a comma");
+            Game::from_str(moves).expect("move string should be valid")
+        })
+        .collect()
+}
+
+/// Evaluates every game with a new engine, returning the total time and number of nodes visited.
+fn bench<T: TranspositionTable>(
+    games: &[Game],
+    new_engine: impl Fn() -> Engine<T>,
+    evaluate: impl Fn(&mut Engine<T>, &Game) -> [Option<i8>; 7],
+) -> (Duration, u64) {
+    let mut total_time = Duration::ZERO;
+    let mut total_nodes = 0;
+
+    for game in games {
+        let mut engine = new_engine();
+
+        let now = Instant::now();
+        evaluate(&mut engine, game);
+        total_time += now.elapsed();
+        total_nodes += engine.node_count();
+    }
+
+    (total_time, total_nodes)
+}
+
+fn print_result(name: &str, time: Duration, nodes: u64) {
+    println!("{name:>15} {BOLD}{time:>10.3?}{RESET} {nodes:>12} nodes");
+}
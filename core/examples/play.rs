@@ -20,6 +20,9 @@ fn main() {
         }
 
         println!("{game}");
+        if let Some((x, y)) = game.last_move_coord() {
+            println!("Last move: column {}, row {}", x + 1, y + 1);
+        }
 
         use Status::*;
         match game.status() {
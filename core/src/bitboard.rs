@@ -13,16 +13,24 @@
 //! ```
 //! 0 is the right-most bit, and 48 is the left-most bit.
 //! There is an extra sentinel row of 0s at the top of the bitboard that denotes the separation of columns.
+//!
+//! Boards with other dimensions follow the same layout, where each column takes up `height + 1` bits.
 
-use crate::{AREA, HEIGHT, WIDTH};
+use crate::{Direction, CONNECT_LENGTH, HEIGHT, WIDTH};
+use alloc::{
+    borrow::Cow,
+    format,
+    string::{String, ToString},
+    vec::Vec,
+};
 
-/// Formats a bitboard into a [`String`].
+/// Formats a bitboard of the given dimensions into a [`String`].
 /// **Note:** The top sentinel row, which does not contain any pieces, is omitted.
-pub(crate) fn format(board: u64) -> String {
-    let mut text = String::with_capacity((AREA * 2).into());
-    for row in (0..HEIGHT).rev() {
-        for col in 0..WIDTH {
-            let index = col * WIDTH + row;
+pub(crate) fn format(board: u64, width: u8, height: u8) -> String {
+    let mut text = String::with_capacity((width * height * 2).into());
+    for row in (0..height).rev() {
+        for col in 0..width {
+            let index = bottom_index(col, height) + row;
             let piece = if (board & (1 << index)) != 0 {
                 '1'
             } else {
@@ -30,7 +38,7 @@ pub(crate) fn format(board: u64) -> String {
             };
 
             text.push(piece);
-            if col != WIDTH - 1 {
+            if col != width - 1 {
                 text.push(' ');
             }
         }
@@ -41,41 +49,181 @@ pub(crate) fn format(board: u64) -> String {
     text
 }
 
-/// A mask representing the bottom row of tiles.
-pub(crate) const BOTTOM_ROW_MASK: u64 = {
-    let mut mask = 0;
+/// Formats a bitboard of the given dimensions into a [`String`] including the top sentinel row, for debugging.
+///
+/// Each row is labeled with its 0-indexed row number from the bottom, and the sentinel row is labeled with `S`.
+pub(crate) fn format_raw(board: u64, width: u8, height: u8) -> String {
+    let label_width = (height - 1).to_string().len();
+    (0..=height)
+        .rev()
+        .map(|row| {
+            let label = if row == height {
+                "S".to_string()
+            } else {
+                row.to_string()
+            };
+            let tiles: Vec<&str> = (0..width)
+                .map(|col| {
+                    if board & (1 << (bottom_index(col, height) + row)) != 0 {
+                        "1"
+                    } else {
+                        "0"
+                    }
+                })
+                .collect();
+            format!("{label:>label_width$} | {}", tiles.join(" "))
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// A mask representing the bottom row of tiles in a standard board.
+pub(crate) const BOTTOM_ROW_MASK: u64 = bottom_row_mask(WIDTH, HEIGHT);
+
+/// A mask representing all the tiles in a standard board.
+pub(crate) const FULL_BOARD_MASK: u64 = full_board_mask(WIDTH, HEIGHT);
+
+/// The number of lines of four in a standard board.
+pub(crate) const NUM_LINES: usize = 69;
+
+/// Masks representing every line of four in a standard board.
+pub(crate) const LINE_MASKS: [u64; NUM_LINES] = {
+    let mut masks = [0; NUM_LINES];
+    let mut i = 0;
     let mut col = 0;
     while col < WIDTH {
-        mask |= bottom_piece_mask(col);
+        let mut row = 0;
+        while row < HEIGHT {
+            let mut d = 0;
+            while d < DIRECTIONS.len() {
+                if let Some(mask) =
+                    line_mask(col, row, DIRECTIONS[d], WIDTH, HEIGHT, CONNECT_LENGTH)
+                {
+                    masks[i] = mask;
+                    i += 1;
+                }
+                d += 1;
+            }
+            row += 1;
+        }
         col += 1;
     }
-    mask
+    assert!(i == NUM_LINES);
+    masks
 };
 
-/// A mask representing all the tiles in a board.
-pub(crate) const FULL_BOARD_MASK: u64 = BOTTOM_ROW_MASK * FIRST_COLUMN_MASK;
+/// Random keys for each player and bit index, used to compute Zobrist hashes of boards.
+///
+/// The keys are generated at compile time with the SplitMix64 algorithm, so they are the same in every build.
+#[cfg(feature = "zobrist")]
+pub(crate) const ZOBRIST_KEYS: [[u64; 64]; 2] = {
+    let mut keys = [[0; 64]; 2];
+    let mut state: u64 = 0;
+    let mut i = 0;
+    while i < 128 {
+        state = state.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = state;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        keys[i / 64][i % 64] = z ^ (z >> 31);
+        i += 1;
+    }
+    keys
+};
+
+/// Every direction of a line of pieces.
+const DIRECTIONS: [Direction; 4] = [
+    Direction::AscendingDiagonal,
+    Direction::DescendingDiagonal,
+    Direction::Horizontal,
+    Direction::Vertical,
+];
+
+/// Returns masks representing every line of `connect` tiles in a board of the given dimensions.
+pub(crate) fn line_masks(width: u8, height: u8, connect: u8) -> Cow<'static, [u64]> {
+    if width == WIDTH && height == HEIGHT && connect == CONNECT_LENGTH {
+        return Cow::Borrowed(&LINE_MASKS);
+    }
+
+    (0..width)
+        .flat_map(|col| (0..height).map(move |row| (col, row)))
+        .flat_map(|(col, row)| {
+            DIRECTIONS
+                .into_iter()
+                .filter_map(move |direction| line_mask(col, row, direction, width, height, connect))
+        })
+        .collect()
+}
+
+/// Returns a mask representing the line of `connect` tiles starting at a 0-indexed tile and going right or up in the given direction,
+/// or [`None`] if the line does not fit in a board of the given dimensions.
+const fn line_mask(
+    col: u8,
+    row: u8,
+    direction: Direction,
+    width: u8,
+    height: u8,
+    connect: u8,
+) -> Option<u64> {
+    let length = connect - 1;
+    let (fits, shift) = match direction {
+        Direction::AscendingDiagonal => (col + length < width && row + length < height, height + 2),
+        Direction::DescendingDiagonal => (col + length < width && row >= length, height),
+        Direction::Horizontal => (col + length < width, height + 1),
+        Direction::Vertical => (row + length < height, 1),
+    };
+    if !fits {
+        return None;
+    }
+
+    let mut line = 0;
+    let mut i = 0;
+    while i < connect {
+        line |= 1 << (shift * i);
+        i += 1;
+    }
+    Some(line << (bottom_index(col, height) + row))
+}
+
+/// Returns a mask representing the bottom row of tiles in a board of the given dimensions.
+pub(crate) const fn bottom_row_mask(width: u8, height: u8) -> u64 {
+    let mut mask = 0;
+    let mut col = 0;
+    while col < width {
+        mask |= bottom_piece_mask(col, height);
+        col += 1;
+    }
+    mask
+}
+
+/// Returns a mask representing all the tiles in a board of the given dimensions.
+pub(crate) const fn full_board_mask(width: u8, height: u8) -> u64 {
+    bottom_row_mask(width, height) * first_column_mask(height)
+}
 
 /// Returns a mask representing the top piece in the given 0-indexed column.
-pub(crate) const fn top_piece_mask(col: u8) -> u64 {
-    1 << (bottom_index(col) + HEIGHT - 1)
+pub(crate) const fn top_piece_mask(col: u8, height: u8) -> u64 {
+    1 << (bottom_index(col, height) + height - 1)
 }
 
 /// Returns a mask representing the bottom piece in the given 0-indexed column.
-pub(crate) const fn bottom_piece_mask(col: u8) -> u64 {
-    1 << bottom_index(col)
+pub(crate) const fn bottom_piece_mask(col: u8, height: u8) -> u64 {
+    1 << bottom_index(col, height)
 }
 
 /// Returns a mask representing the tiles in the given 0-indexed column.
-pub(crate) const fn column_mask(col: u8) -> u64 {
-    FIRST_COLUMN_MASK << bottom_index(col)
+pub(crate) const fn column_mask(col: u8, height: u8) -> u64 {
+    first_column_mask(height) << bottom_index(col, height)
 }
 
-/// A mask representing the tiles in the first column.
-const FIRST_COLUMN_MASK: u64 = (1 << HEIGHT) - 1;
+/// Returns a mask representing the tiles in the first column.
+const fn first_column_mask(height: u8) -> u64 {
+    (1 << height) - 1
+}
 
 /// Returns the index of the bottom tile of a column.
-const fn bottom_index(col: u8) -> u8 {
-    col * (HEIGHT + 1)
+const fn bottom_index(col: u8, height: u8) -> u8 {
+    col * (height + 1)
 }
 
 #[cfg(test)]
@@ -92,10 +240,30 @@ mod tests {
         // 0 1 1 0 1 1 1
         // 1 1 0 1 1 1 0
         let expected = "0 1 1 1 0 1 1\n1 1 1 0 1 1 0\n1 1 0 0 1 0 0\n0 0 1 0 0 1 1\n0 1 1 0 1 1 1\n1 1 0 1 1 1 0";
-        let actual = format(0b_0100110_0110111_0011011_0100001_0110110_0111011_0011001);
+        let actual = format(
+            0b_0100110_0110111_0011011_0100001_0110110_0111011_0011001,
+            WIDTH,
+            HEIGHT,
+        );
         assert_eq!(expected, actual);
     }
 
+    #[test]
+    fn format_raw_string() {
+        // The top of the first column is in the sentinel row
+        let board = 0b_0000000_0000000_0000000_0000000_0000000_0000001_1000001;
+
+        let expected = "0 0 0 0 0 0 0\n0 0 0 0 0 0 0\n0 0 0 0 0 0 0\n0 0 0 0 0 0 0\n0 0 0 0 0 0 0\n1 1 0 0 0 0 0";
+        assert_eq!(format(board, WIDTH, HEIGHT), expected);
+
+        let expected = "S | 1 0 0 0 0 0 0\n5 | 0 0 0 0 0 0 0\n4 | 0 0 0 0 0 0 0\n3 | 0 0 0 0 0 0 0\n2 | 0 0 0 0 0 0 0\n1 | 0 0 0 0 0 0 0\n0 | 1 1 0 0 0 0 0";
+        assert_eq!(format_raw(board, WIDTH, HEIGHT), expected);
+
+        // Labels are padded to the width of the largest row number
+        let expected = " S | 0 0 0 0\n10 | 0 0 0 0";
+        assert!(format_raw(0, 4, 11).starts_with(expected));
+    }
+
     #[test]
     fn masks() {
         // 0 0 0 0 0 0 0
@@ -127,7 +295,7 @@ mod tests {
         // 0 0 0 0 0 0 0
         // 0 0 0 0 0 0 0
         assert_eq!(
-            top_piece_mask(3),
+            top_piece_mask(3, HEIGHT),
             0b_0000000_0000000_0000000_0100000_0000000_0000000_0000000
         );
 
@@ -138,7 +306,7 @@ mod tests {
         // 0 0 0 0 0 0 0
         // 0 0 0 1 0 0 0
         assert_eq!(
-            bottom_piece_mask(3),
+            bottom_piece_mask(3, HEIGHT),
             0b_0000000_0000000_0000000_0000001_0000000_0000000_0000000
         );
 
@@ -149,8 +317,38 @@ mod tests {
         // 0 0 0 1 0 0 0
         // 0 0 0 1 0 0 0
         assert_eq!(
-            column_mask(3),
+            column_mask(3, HEIGHT),
             0b_0000000_0000000_0000000_0111111_0000000_0000000_0000000
         );
     }
+
+    #[test]
+    fn lines() {
+        assert_eq!(LINE_MASKS.len(), 69);
+        assert!(LINE_MASKS.iter().all(|mask| mask.count_ones() == 4));
+        assert!(LINE_MASKS.iter().all(|mask| mask & !FULL_BOARD_MASK == 0));
+
+        let mut unique = LINE_MASKS.to_vec();
+        unique.sort_unstable();
+        unique.dedup();
+        assert_eq!(unique.len(), 69);
+
+        // 0 0 0 0 0 0 0
+        // 0 0 0 0 0 0 0
+        // 0 0 0 1 0 0 0
+        // 0 0 1 0 0 0 0
+        // 0 1 0 0 0 0 0
+        // 1 0 0 0 0 0 0
+        assert!(LINE_MASKS.contains(&0b_0000000_0000000_0000000_0001000_0000100_0000010_0000001));
+
+        // 4 horizontal lines in each row, 1 vertical line in each column, and 1 line in each diagonal direction
+        let masks = line_masks(4, 4, 4);
+        assert_eq!(masks.len(), 10);
+        assert!(masks.iter().all(|mask| mask & !full_board_mask(4, 4) == 0));
+
+        // 3 horizontal lines of five in each row, 2 vertical lines in each column, and 6 lines in each diagonal direction
+        let masks = line_masks(7, 6, 5);
+        assert_eq!(masks.len(), 18 + 14 + 6 * 2);
+        assert!(masks.iter().all(|mask| mask.count_ones() == 5));
+    }
 }
@@ -1,14 +1,7 @@
-use crate::{bitboard, Game, AREA, HEIGHT, WIDTH};
-
-pub(crate) enum WinDirection {
-    AscendingDiagonal,
-    DescendingDiagonal,
-    Horizontal,
-    Vertical,
-}
+use crate::{bitboard, Direction, Game, CONNECT_LENGTH, HEIGHT, WIDTH};
 
 /// Internal representation of a Connect Four board. In the [`Engine`], this serves as a node in the game tree.
-#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
 pub(crate) struct Board {
     /// A bitboard representing the pieces belonging to the current player.
     player_bb: u64,
@@ -16,6 +9,36 @@ pub(crate) struct Board {
     occupied_bb: u64,
     /// The number of moves made in the game.
     num_moves: u8,
+    /// The number of columns in the board.
+    width: u8,
+    /// The number of rows in the board.
+    height: u8,
+    /// The number of pieces in a row needed to win.
+    connect: u8,
+    /// A mask representing the bottom row of tiles.
+    bottom_mask: u64,
+    /// A mask representing all the tiles in the board.
+    full_mask: u64,
+    /// A Zobrist hash of the pieces, updated incrementally as moves are played and undone.
+    #[cfg(feature = "zobrist")]
+    zobrist: u64,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            player_bb: 0,
+            occupied_bb: 0,
+            num_moves: 0,
+            width: WIDTH,
+            height: HEIGHT,
+            connect: CONNECT_LENGTH,
+            bottom_mask: bitboard::BOTTOM_ROW_MASK,
+            full_mask: bitboard::FULL_BOARD_MASK,
+            #[cfg(feature = "zobrist")]
+            zobrist: 0,
+        }
+    }
 }
 
 impl Board {
@@ -23,6 +46,62 @@ impl Board {
         Self::default()
     }
 
+    /// Creates an empty board with the given dimensions, assuming that they fit inside a bitboard.
+    pub(crate) fn with_dimensions(width: u8, height: u8) -> Self {
+        Self {
+            width,
+            height,
+            bottom_mask: bitboard::bottom_row_mask(width, height),
+            full_mask: bitboard::full_board_mask(width, height),
+            ..Self::default()
+        }
+    }
+
+    /// Creates an empty board with the given dimensions and number of pieces in a row needed to win, assuming that they fit inside a bitboard.
+    pub(crate) fn with_connect_length(width: u8, height: u8, connect: u8) -> Self {
+        Self {
+            connect,
+            ..Self::with_dimensions(width, height)
+        }
+    }
+
+    /// Creates a standard board from bitboards of the current player's pieces and all pieces, without checking if the position is reachable.
+    pub(crate) fn from_bitboards(player_bb: u64, occupied_bb: u64) -> Self {
+        #[allow(unused_mut)]
+        let mut board = Self {
+            player_bb,
+            occupied_bb,
+            num_moves: occupied_bb.count_ones().try_into().unwrap(),
+            ..Self::default()
+        };
+
+        #[cfg(feature = "zobrist")]
+        {
+            board.zobrist = board.compute_zobrist();
+        }
+        board
+    }
+
+    /// Returns the number of columns in the board.
+    pub(crate) fn width(&self) -> u8 {
+        self.width
+    }
+
+    /// Returns the number of rows in the board.
+    pub(crate) fn height(&self) -> u8 {
+        self.height
+    }
+
+    /// Returns the number of pieces in a row needed to win.
+    pub(crate) fn connect(&self) -> u8 {
+        self.connect
+    }
+
+    /// Returns the number of tiles in the board.
+    pub(crate) fn area(&self) -> u8 {
+        self.width * self.height
+    }
+
     /// Returns a bitboard representing the pieces belonging to the current player.
     pub(crate) fn player_bb(&self) -> u64 {
         self.player_bb
@@ -41,32 +120,90 @@ impl Board {
 
     /// Plays the current player's piece in the given 0-indexed column without checking if the move can be played.
     pub(crate) fn play_unchecked(&mut self, col: u8) {
-        self.play_bb(self.occupied_bb + bitboard::bottom_piece_mask(col));
+        self.play_bb(self.occupied_bb + bitboard::bottom_piece_mask(col, self.height));
     }
 
     /// Plays the current player's piece given a move represented as a bitboard.
     pub(crate) fn play_bb(&mut self, move_bb: u64) {
+        #[cfg(feature = "zobrist")]
+        {
+            // The move may include tiles that are already occupied
+            self.zobrist ^= Self::zobrist_key(self.num_moves, move_bb & !self.occupied_bb);
+        }
+
         self.player_bb ^= self.occupied_bb;
         self.occupied_bb |= move_bb;
         self.num_moves += 1;
     }
 
+    /// Plays the current player's piece given a move represented as a bitboard like [`play_bb`](Board::play_bb),
+    /// returning whether the move completed a winning line.
+    ///
+    /// Only the placed piece can complete a line, so this checks if its tile was one of the current player's winning tiles before the move,
+    /// which is faster than checking the whole board with [`check_win`](Board::check_win) afterwards.
+    pub(crate) fn play_and_check(&mut self, move_bb: u64) -> bool {
+        let won = self.winning_bb(self.player_bb) & move_bb != 0;
+        self.play_bb(move_bb);
+        won
+    }
+
     /// Removes the topmost piece in the given 0-indexed column.
     pub(crate) fn undo_unchecked(&mut self, col: u8) {
-        let move_bb = (self.occupied_bb + bitboard::bottom_piece_mask(col)) >> 1;
-        self.occupied_bb ^= move_bb & bitboard::column_mask(col);
+        let move_bb = ((self.occupied_bb + bitboard::bottom_piece_mask(col, self.height)) >> 1)
+            & bitboard::column_mask(col, self.height);
+        self.occupied_bb ^= move_bb;
         self.player_bb ^= self.occupied_bb;
         self.num_moves -= 1;
+
+        #[cfg(feature = "zobrist")]
+        {
+            self.zobrist ^= Self::zobrist_key(self.num_moves, move_bb);
+        }
+    }
+
+    /// Removes the bottom piece in the given 0-indexed column and shifts the rest of the column down, passing the turn to the opponent,
+    /// without checking if the piece belongs to the current player.
+    pub(crate) fn pop_unchecked(&mut self, col: u8) {
+        let column = bitboard::column_mask(col, self.height);
+        let bottom = bitboard::bottom_piece_mask(col, self.height);
+        let shift_down = |bb: u64| (bb & !column) | ((bb & column & !bottom) >> 1);
+
+        self.occupied_bb = shift_down(self.occupied_bb);
+        self.player_bb = shift_down(self.player_bb) ^ self.occupied_bb;
+        self.num_moves += 1;
+
+        #[cfg(feature = "zobrist")]
+        {
+            // Every piece in the column moves, so the hash is computed again
+            self.zobrist = self.compute_zobrist();
+        }
+    }
+
+    /// Reverses [`pop_unchecked`](Board::pop_unchecked), shifting the given 0-indexed column up and putting the opponent's piece back at the bottom,
+    /// then passing the turn back to the opponent.
+    pub(crate) fn unpop_unchecked(&mut self, col: u8) {
+        let column = bitboard::column_mask(col, self.height);
+        let bottom = bitboard::bottom_piece_mask(col, self.height);
+        let shift_up = |bb: u64| (bb & !column) | ((bb & column) << 1) | bottom;
+
+        self.player_bb = shift_up(self.player_bb ^ self.occupied_bb);
+        self.occupied_bb = shift_up(self.occupied_bb);
+        self.num_moves -= 1;
+
+        #[cfg(feature = "zobrist")]
+        {
+            self.zobrist = self.compute_zobrist();
+        }
     }
 
     /// Checks if the given 0-indexed column is not full, assuming that `col` is inside the game board.
     pub(crate) fn is_open(&self, col: u8) -> bool {
-        (self.occupied_bb & bitboard::top_piece_mask(col)) == 0
+        (self.occupied_bb & bitboard::top_piece_mask(col, self.height)) == 0
     }
 
     /// Returns the number of pieces in a 0-indexed column.
     pub(crate) fn pieces_in_col(&self, col: u8) -> u8 {
-        (self.occupied_bb & bitboard::column_mask(col))
+        (self.occupied_bb & bitboard::column_mask(col, self.height))
             .count_ones()
             .try_into()
             .unwrap()
@@ -74,7 +211,12 @@ impl Board {
 
     /// Checks if the board is full and no more moves can be played.
     pub(crate) fn is_full(&self) -> bool {
-        self.num_moves >= AREA
+        self.occupied_bb == self.full_mask
+    }
+
+    /// Returns the number of pieces on the board, which is less than the number of moves if any pieces were popped out.
+    pub(crate) fn num_pieces(&self) -> u8 {
+        self.occupied_bb.count_ones().try_into().unwrap()
     }
 
     pub(crate) fn is_terminal(&self) -> bool {
@@ -85,45 +227,51 @@ impl Board {
         self.opponent_winning_bb().is_some()
     }
 
-    pub(crate) fn opponent_winning_bb(&self) -> Option<(u64, WinDirection)> {
-        self.check_win(self.opponent_bb())
+    /// Checks if neither player can ever win, because every line on the board contains pieces of both players.
+    pub(crate) fn is_dead_draw(&self) -> bool {
+        let opponent_bb = self.opponent_bb();
+        bitboard::line_masks(self.width, self.height, self.connect)
+            .iter()
+            .all(|mask| mask & self.player_bb != 0 && mask & opponent_bb != 0)
     }
 
-    pub(crate) fn check_win(&self, bitboard: u64) -> Option<(u64, WinDirection)> {
-        use WinDirection::*;
-
-        // Ascending diagonal /
-        let x = bitboard & (bitboard >> (HEIGHT + 2));
-        let new_bitboard = x & (x >> ((HEIGHT + 2) * 2));
-        if new_bitboard != 0 {
-            return Some((new_bitboard, AscendingDiagonal));
-        }
+    pub(crate) fn opponent_winning_bb(&self) -> Option<(u64, Direction)> {
+        self.check_win(self.opponent_bb())
+    }
 
-        // Descending diagonal \
-        let x = bitboard & (bitboard >> HEIGHT);
-        let new_bitboard = x & (x >> (HEIGHT * 2));
-        if new_bitboard != 0 {
-            return Some((new_bitboard, DescendingDiagonal));
-        }
+    pub(crate) fn check_win(&self, bitboard: u64) -> Option<(u64, Direction)> {
+        use Direction::*;
 
-        // Horizontal -
-        let x = bitboard & (bitboard >> (HEIGHT + 1));
-        let new_bitboard = x & (x >> ((HEIGHT + 1) * 2));
-        if new_bitboard != 0 {
-            return Some((new_bitboard, Horizontal));
-        }
+        [AscendingDiagonal, DescendingDiagonal, Horizontal, Vertical]
+            .into_iter()
+            .map(|direction| (self.lines_bb(bitboard, direction), direction))
+            .find(|&(lines, _)| lines != 0)
+    }
 
-        // Vertical |
-        let x = bitboard & (bitboard >> 1);
-        let new_bitboard = x & (x >> 2);
-        if new_bitboard != 0 {
-            return Some((new_bitboard, Vertical));
-        }
-        None
+    /// Returns a bitboard of the first tiles of every winning line in a given direction, where the first tile is the one with the lowest index.
+    pub(crate) fn lines_bb(&self, bitboard: u64, direction: Direction) -> u64 {
+        let shift = match direction {
+            // Ascending diagonal /
+            Direction::AscendingDiagonal => self.height + 2,
+            // Descending diagonal \
+            Direction::DescendingDiagonal => self.height,
+            // Horizontal -
+            Direction::Horizontal => self.height + 1,
+            // Vertical |
+            Direction::Vertical => 1,
+        };
+
+        (1..self.connect).fold(bitboard, |lines, i| {
+            lines
+                & bitboard
+                    .checked_shr(u32::from(shift) * u32::from(i))
+                    .unwrap_or(0)
+        })
     }
 
     /// Returns a bitboard of the playable moves that do not give the opponent an immediate win.
     /// If there are no possible moves that allow the current player to survive, then 0 is returned.
+    #[cfg(feature = "std")]
     pub(crate) fn non_losing_moves_bb(&self) -> u64 {
         let possible_moves = self.possible_bb();
         let opponent_win = self.winning_bb(self.opponent_bb());
@@ -144,65 +292,107 @@ impl Board {
     }
 
     /// Returns a bitboard of available moves.
-    fn possible_bb(&self) -> u64 {
-        (self.occupied_bb + bitboard::BOTTOM_ROW_MASK) & bitboard::FULL_BOARD_MASK
+    pub(crate) fn possible_bb(&self) -> u64 {
+        (self.occupied_bb + self.bottom_mask) & self.full_mask
     }
 
     /// Checks whether the current player can win with their next move.
+    #[cfg(feature = "std")]
     pub(crate) fn can_win_next(&self) -> bool {
         self.winning_bb(self.player_bb) & self.possible_bb() != 0
     }
 
     /// Checks whether the current player can win by playing into a 0-indexed column.
     pub(crate) fn is_winning_move(&self, col: u8) -> bool {
-        self.winning_bb(self.player_bb) & self.possible_bb() & bitboard::column_mask(col) != 0
+        self.winning_bb(self.player_bb)
+            & self.possible_bb()
+            & bitboard::column_mask(col, self.height)
+            != 0
+    }
+
+    /// Returns a bitboard of the playable tiles that complete a winning line for the pieces in `bitboard`.
+    pub(crate) fn playable_winning_bb(&self, bitboard: u64) -> u64 {
+        self.winning_bb(bitboard) & self.possible_bb()
     }
 
     /// Returns the number of winning moves the current player has after playing a given move.
+    #[cfg(feature = "std")]
     pub(crate) fn count_winning_moves(&self, move_bb: u64) -> u32 {
         self.winning_bb(self.player_bb | move_bb).count_ones()
     }
 
     /// Returns a bitboard of tiles that can be played to win the game.
-    fn winning_bb(&self, bitboard: u64) -> u64 {
+    pub(crate) fn winning_bb(&self, bitboard: u64) -> u64 {
+        if self.connect != CONNECT_LENGTH {
+            return self.winning_bb_any_length(bitboard);
+        }
+
         // Vertical |
         let mut x = (bitboard << 1) & (bitboard << 2) & (bitboard << 3);
 
         // Ascending diagonal /
-        let y = (bitboard << HEIGHT) & (bitboard << (2 * HEIGHT));
-        x |= y & (bitboard << (3 * (HEIGHT)));
-        x |= y & (bitboard >> (HEIGHT));
+        let y = (bitboard << self.height) & (bitboard << (2 * self.height));
+        x |= y & (bitboard << (3 * (self.height)));
+        x |= y & (bitboard >> (self.height));
 
-        let y = (bitboard >> (HEIGHT)) & (bitboard >> (2 * HEIGHT));
-        x |= y & (bitboard >> (3 * (HEIGHT)));
-        x |= y & (bitboard << (HEIGHT));
+        let y = (bitboard >> (self.height)) & (bitboard >> (2 * self.height));
+        x |= y & (bitboard >> (3 * (self.height)));
+        x |= y & (bitboard << (self.height));
 
         // Horizontal -
-        let y = (bitboard << (HEIGHT + 1)) & (bitboard << (2 * (HEIGHT + 1)));
-        x |= y & (bitboard << (3 * (HEIGHT + 1)));
-        x |= y & (bitboard >> (HEIGHT + 1));
+        let y = (bitboard << (self.height + 1)) & (bitboard << (2 * (self.height + 1)));
+        x |= y & (bitboard << (3 * (self.height + 1)));
+        x |= y & (bitboard >> (self.height + 1));
 
-        let y = (bitboard >> (HEIGHT + 1)) & (bitboard >> (2 * (HEIGHT + 1)));
-        x |= y & (bitboard >> (3 * (HEIGHT + 1)));
-        x |= y & (bitboard << (HEIGHT + 1));
+        let y = (bitboard >> (self.height + 1)) & (bitboard >> (2 * (self.height + 1)));
+        x |= y & (bitboard >> (3 * (self.height + 1)));
+        x |= y & (bitboard << (self.height + 1));
 
         // Descending diagonal \
-        let y = (bitboard << (HEIGHT + 2)) & (bitboard << (2 * (HEIGHT + 2)));
-        x |= y & (bitboard << (3 * (HEIGHT + 2)));
-        x |= y & (bitboard >> (HEIGHT + 2));
+        let y = (bitboard << (self.height + 2)) & (bitboard << (2 * (self.height + 2)));
+        x |= y & (bitboard << (3 * (self.height + 2)));
+        x |= y & (bitboard >> (self.height + 2));
 
-        let y = (bitboard >> (HEIGHT + 2)) & (bitboard >> (2 * (HEIGHT + 2)));
-        x |= y & (bitboard >> (3 * (HEIGHT + 2)));
-        x |= y & (bitboard << (HEIGHT + 2));
+        let y = (bitboard >> (self.height + 2)) & (bitboard >> (2 * (self.height + 2)));
+        x |= y & (bitboard >> (3 * (self.height + 2)));
+        x |= y & (bitboard << (self.height + 2));
 
-        x & (self.occupied_bb ^ bitboard::FULL_BOARD_MASK)
+        x & (self.occupied_bb ^ self.full_mask)
     }
 
+    /// Returns a bitboard of tiles that can be played to win the game with any number of pieces in a row,
+    /// which is slower than the shift and mask chains specialized for four in a row.
+    fn winning_bb_any_length(&self, bitboard: u64) -> u64 {
+        // Moves the pieces `offset` tiles along a line, so a tile is set if it has a piece `offset` tiles before it
+        let shifted = |offset: i32| -> u64 {
+            if offset >= 0 {
+                bitboard.checked_shl(offset.unsigned_abs()).unwrap_or(0)
+            } else {
+                bitboard.checked_shr(offset.unsigned_abs()).unwrap_or(0)
+            }
+        };
+        let connect = i32::from(self.connect);
+        let height = i32::from(self.height);
+
+        let mut x = 0;
+        for shift in [1, height, height + 1, height + 2] {
+            // Tiles that complete a line as its `position`th tile
+            for position in 0..connect {
+                x |= (0..connect)
+                    .filter(|&i| i != position)
+                    .fold(!0, |line, i| line & shifted((position - i) * shift));
+            }
+        }
+
+        x & (self.occupied_bb ^ self.full_mask)
+    }
+
+    #[cfg(feature = "std")]
     pub(crate) fn position_score(&self, win_this_turn: bool) -> i8 {
         if win_this_turn {
-            (AREA - self.num_moves + 1) as i8 / 2
+            (self.area() - self.num_moves + 1) as i8 / 2
         } else {
-            (AREA - self.num_moves) as i8 / 2
+            (self.area() - self.num_moves) as i8 / 2
         }
     }
 
@@ -211,11 +401,36 @@ impl Board {
         self.player_bb + self.occupied_bb
     }
 
+    /// Returns the board reflected horizontally, swapping the first column with the last column and so on.
+    pub(crate) fn mirror(&self) -> Self {
+        let mut mirrored = Self {
+            player_bb: 0,
+            occupied_bb: 0,
+            ..*self
+        };
+
+        for col in 0..self.width {
+            let from = col * (self.height + 1);
+            let to = (self.width - 1 - col) * (self.height + 1);
+            let mask = bitboard::column_mask(col, self.height);
+
+            mirrored.player_bb |= ((self.player_bb & mask) >> from) << to;
+            mirrored.occupied_bb |= ((self.occupied_bb & mask) >> from) << to;
+        }
+
+        #[cfg(feature = "zobrist")]
+        {
+            mirrored.zobrist = mirrored.compute_zobrist();
+        }
+        mirrored
+    }
+
     /// Returns a symmetric base 3 key for the current game state.
+    #[cfg(feature = "std")]
     pub(crate) fn key3(&self) -> u128 {
-        let key_forward = (0..WIDTH).fold(0, |key, col| self.partial_key3(key, col));
+        let key_forward = (0..self.width).fold(0, |key, col| self.partial_key3(key, col));
 
-        let key_backward = (0..WIDTH)
+        let key_backward = (0..self.width)
             .rev()
             .fold(0, |key, col| self.partial_key3(key, col));
 
@@ -226,8 +441,9 @@ impl Board {
         }
     }
 
+    #[cfg(feature = "std")]
     fn partial_key3(&self, mut key: u128, col: u8) -> u128 {
-        let mut mask = bitboard::bottom_piece_mask(col);
+        let mut mask = bitboard::bottom_piece_mask(col, self.height);
         while (self.occupied_bb & mask) != 0 {
             key *= 3;
             if (self.player_bb & mask) == 0 {
@@ -244,6 +460,37 @@ impl Board {
     pub(crate) fn num_moves(&self) -> u8 {
         self.num_moves
     }
+
+    /// Returns a Zobrist hash of the pieces on the board, which is updated incrementally as moves are played and undone.
+    #[cfg(feature = "zobrist")]
+    pub(crate) fn zobrist(&self) -> u64 {
+        self.zobrist
+    }
+
+    /// Computes the Zobrist hash of the pieces on the board from scratch.
+    #[cfg(feature = "zobrist")]
+    fn compute_zobrist(&self) -> u64 {
+        let mut hash = 0;
+        let mut pieces = self.occupied_bb;
+        while pieces != 0 {
+            let piece_bb = pieces & pieces.wrapping_neg();
+            // The current player's pieces were played on moves with the same parity as the number of moves
+            let num_moves = if self.player_bb & piece_bb != 0 {
+                self.num_moves
+            } else {
+                self.num_moves + 1
+            };
+            hash ^= Self::zobrist_key(num_moves, piece_bb);
+            pieces ^= piece_bb;
+        }
+        hash
+    }
+
+    /// Returns the Zobrist key of a piece played on a move with the given number of moves before it, given as a single-bit bitboard.
+    #[cfg(feature = "zobrist")]
+    fn zobrist_key(num_moves: u8, piece_bb: u64) -> u64 {
+        bitboard::ZOBRIST_KEYS[usize::from(num_moves % 2)][piece_bb.trailing_zeros() as usize]
+    }
 }
 
 impl From<&Game> for Board {
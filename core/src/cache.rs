@@ -1,15 +1,57 @@
-use crate::{AREA, MAX_SCORE, MIN_SCORE};
+use crate::{Board, Engine, Game, AREA, MAX_SCORE, MIN_SCORE};
 use std::collections::HashMap;
+use std::sync::{Arc, Mutex, MutexGuard};
 
 /// Separates the base-3 keys of positions in a cache buffer.
 ///
 /// This should be a value that is impossible to produce from any game state.
 pub const BUFFER_DELIMIT: u32 = 1;
 
+/// The first byte of the current bytes format of a [`Cache`], which stores keys as u64s.
+///
+/// This is larger than the maximum depth of any cache, so it distinguishes the current format from the original format that started with the maximum depth.
+pub const BYTES_FORMAT_VERSION: u8 = 0xF2;
+
+/// The maximum depth of an opening book created by [`Cache::build`].
+///
+/// A position with `n` moves has a base-3 key with up to `n + 6` digits, so keys of deeper positions may not fit in 64 bits.
+pub const MAX_BOOK_DEPTH: u8 = 34;
+
+/// The number of independently-locked shards in a [`ConcurrentCache`].
+const NUM_SHARDS: usize = 64;
+
+/// A table associating keys and scores of previously-computed positions, used by the [`Engine`](crate::Engine) as its transposition table.
+pub trait TranspositionTable {
+    /// Returns the score of a given position's key or [`None`] if the key does not exist in the table.
+    fn get(&self, key: &u64) -> Option<i8>;
+
+    /// Inserts a position's key and its score as a key-value pair into the table.
+    fn insert(&mut self, key: u64, value: i8);
+
+    /// Clears the table, removing all key-value pairs.
+    fn clear(&mut self);
+
+    /// Returns the number of elements in the table.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the table is empty.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Returns a table for another search thread to use, which is either a new empty table or a handle to this table if it can be shared between threads.
+    fn fork(&self) -> Self
+    where
+        Self: Sized;
+}
+
 /// A cache associating keys and scores of previously-computed positions.
+#[derive(Clone)]
 pub struct Cache {
     max_depth: u8,
     table: HashMap<u64, i8>,
+    /// The maximum number of entries in the cache, or [`None`] if the cache is unbounded.
+    capacity: Option<usize>,
 }
 
 impl Default for Cache {
@@ -17,6 +59,7 @@ impl Default for Cache {
         Self {
             max_depth: AREA,
             table: HashMap::new(),
+            capacity: None,
         }
     }
 }
@@ -30,39 +73,188 @@ impl Cache {
         }
     }
 
+    /// Creates an empty cache that holds at most `capacity` entries, keeping memory usage bounded during long searches.
+    ///
+    /// When a new key is inserted into a full cache, all existing entries are evicted.
+    ///
+    /// # Panics
+    /// Panics if `capacity` is 0.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Cache;
+    ///
+    /// let mut cache = Cache::with_capacity(8, 2);
+    /// cache.insert(1, 0);
+    /// cache.insert(2, 0);
+    /// cache.insert(3, 0);
+    /// assert_eq!(cache.len(), 1);
+    /// assert_eq!(cache.capacity(), Some(2));
+    /// ```
+    pub fn with_capacity(max_depth: u8, capacity: usize) -> Self {
+        assert!(capacity > 0, "with_capacity: capacity must be positive");
+        Self {
+            max_depth,
+            capacity: Some(capacity),
+            ..Self::default()
+        }
+    }
+
+    /// Creates an opening book by solving every unique position with at most `max_depth` moves, using an engine to solve the positions.
+    ///
+    /// Positions that are mirror images of each other share a base-3 key, so only one of them is solved.
+    /// Positions where the current player can win immediately are not included, because the engine never looks them up.
+    /// The book can be saved with [`to_bytes`](Cache::to_bytes) and loaded with [`from_bytes`](Cache::from_bytes).
+    ///
+    /// # Warning
+    /// Building a book is intended to be done offline. The shallowest positions are the most expensive to solve, and solving the empty board alone
+    /// takes several minutes and fills an unbounded transposition table beyond the available memory of most machines,
+    /// so use an engine with a bounded transposition table (see [`EngineBuilder::tt_capacity`](crate::EngineBuilder::tt_capacity)).
+    /// Each additional depth multiplies the number of positions to solve by up to 7 (see [`Game::perft`]).
+    /// The opening book of the engine is used while building, so a shallower book speeds up building a deeper one.
+    ///
+    /// # Panics
+    /// Panics if `max_depth` is larger than [`MAX_BOOK_DEPTH`].
+    pub fn build<T: crate::TranspositionTable>(engine: &mut Engine<T>, max_depth: u8) -> Self {
+        Self::build_from(engine, Board::new(), max_depth)
+    }
+
+    /// Creates an opening book of every unique position after a board with at most `max_depth` moves.
+    fn build_from<T: crate::TranspositionTable>(
+        engine: &mut Engine<T>,
+        board: Board,
+        max_depth: u8,
+    ) -> Self {
+        assert!(
+            max_depth <= MAX_BOOK_DEPTH,
+            "build: max_depth is too high (maximum {})",
+            MAX_BOOK_DEPTH
+        );
+
+        let mut cache = Self::new(max_depth);
+        for depth in (board.num_moves()..=max_depth).rev() {
+            for position in Game::unique_positions(board, depth - board.num_moves(), Board::key3) {
+                if !position.can_win_next() {
+                    let key3 = position
+                        .key3()
+                        .try_into()
+                        .expect("base-3 key should fit in 64 bits");
+                    cache.insert(key3, engine.solve(position));
+                }
+            }
+        }
+        cache
+    }
+
     /// Creates a cache from a vector of bytes, returning [`None`] if the bytes do not have the correct format described below.
     ///
     /// # Bytes Format
-    /// - First byte: the maximum depth of the cache.
-    /// - Remaining bytes: buffers of little-endian u32s, representing base-3 keys.
-    /// Each buffer is delimited by [`BUFFER_DELIMIT`] and is associated with a score, starting from [`MIN_SCORE`] incrementing up to [`MAX_SCORE`].
+    /// - First byte: [`BYTES_FORMAT_VERSION`].
+    /// - Second byte: the maximum depth of the cache.
+    /// - Remaining bytes: buffers of little-endian u64s, representing keys.
+    ///   Each buffer is delimited by [`BUFFER_DELIMIT`] and is associated with a score, starting from [`MIN_SCORE`] incrementing up to [`MAX_SCORE`].
+    ///
+    /// The original format is also read, which starts with the maximum depth instead of a version and stores keys as u32s.
     pub fn from_bytes(bytes: Vec<u8>) -> Option<Self> {
-        let (max_depth, rest_bytes) = bytes.split_first()?;
-        let mut cache = Self::new(*max_depth);
+        match bytes.split_first()? {
+            (&BYTES_FORMAT_VERSION, rest_bytes) => {
+                let (max_depth, rest_bytes) = rest_bytes.split_first()?;
+                let keys = rest_bytes
+                    .chunks_exact(8)
+                    .map(|chunk| u64::from_le_bytes(chunk.try_into().unwrap()));
+                Self::from_buffers(*max_depth, keys)
+            }
+            (max_depth, rest_bytes) => {
+                let keys = rest_bytes
+                    .chunks_exact(4)
+                    .map(|chunk| u32::from_le_bytes(chunk.try_into().unwrap()).into());
+                Self::from_buffers(*max_depth, keys)
+            }
+        }
+    }
+
+    /// Creates a cache from keys in buffers delimited by [`BUFFER_DELIMIT`], returning [`None`] if a buffer is missing.
+    fn from_buffers(max_depth: u8, keys: impl Iterator<Item = u64>) -> Option<Self> {
+        let mut cache = Self::new(max_depth);
 
         let mut score = MIN_SCORE;
-        for chunk in rest_bytes.chunks_exact(4) {
-            let key3 = u32::from_le_bytes(chunk.try_into().unwrap());
-            if key3 == BUFFER_DELIMIT {
+        for key in keys {
+            if key == u64::from(BUFFER_DELIMIT) {
                 if score == MAX_SCORE {
                     return Some(cache);
                 }
                 score += 1;
             } else {
-                cache.insert(key3.into(), score);
+                cache.insert(key, score);
             }
         }
 
         None
     }
 
+    /// Converts the cache into a vector of bytes in the format read by [`from_bytes`](Cache::from_bytes).
+    ///
+    /// Both the base-3 keys of an opening book and the keys of a transposition table can be saved,
+    /// so a table filled by one search can be loaded again to warm-start another.
+    ///
+    /// # Panics
+    /// Panics if a key is [`BUFFER_DELIMIT`] or a score is not between [`MIN_SCORE`] and [`MAX_SCORE`], which never happens for the positions of a game.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Cache;
+    ///
+    /// let mut cache = Cache::new(8);
+    /// cache.insert(12345, -2);
+    /// cache.insert(1 << 48, 5);
+    ///
+    /// let copy = Cache::from_bytes(cache.to_bytes()).unwrap();
+    /// assert_eq!(copy.max_depth(), 8);
+    /// assert_eq!(copy.get(&12345), Some(-2));
+    /// assert_eq!(copy.get(&(1 << 48)), Some(5));
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut buffers = vec![Vec::new(); (MAX_SCORE - MIN_SCORE + 1) as usize];
+        for (&key, &score) in &self.table {
+            assert!(
+                key != u64::from(BUFFER_DELIMIT),
+                "to_bytes: key is the buffer delimiter"
+            );
+            assert!(
+                (MIN_SCORE..=MAX_SCORE).contains(&score),
+                "to_bytes: score is out of range"
+            );
+            buffers[(score - MIN_SCORE) as usize].push(key);
+        }
+
+        let mut bytes = Vec::with_capacity(2 + (self.len() + buffers.len()) * 8);
+        bytes.push(BYTES_FORMAT_VERSION);
+        bytes.push(self.max_depth);
+
+        for mut buffer in buffers {
+            buffer.sort_unstable();
+            for key in buffer {
+                bytes.extend_from_slice(&key.to_le_bytes());
+            }
+            bytes.extend_from_slice(&u64::from(BUFFER_DELIMIT).to_le_bytes());
+        }
+
+        bytes
+    }
+
     /// Returns the score of a given position's key or [`None`](Option::None) if the key does not exist in the cache.
     pub fn get(&self, key: &u64) -> Option<i8> {
         self.table.get(key).copied()
     }
 
     /// Inserts a position's key and its score as a key-value pair into the cache.
+    /// If the cache is full and the key is not already present, all existing entries are evicted first.
     pub fn insert(&mut self, key: u64, value: i8) {
+        if let Some(capacity) = self.capacity {
+            if self.table.len() >= capacity && !self.table.contains_key(&key) {
+                self.table.clear();
+            }
+        }
         self.table.insert(key, value);
     }
 
@@ -81,8 +273,288 @@ impl Cache {
         self.table.len()
     }
 
+    /// Returns the maximum number of entries in the cache, or [`None`] if the cache is unbounded.
+    pub fn capacity(&self) -> Option<usize> {
+        self.capacity
+    }
+
     /// Returns the maximum depth of the cache for optimization purposes.
     pub fn max_depth(&self) -> u8 {
         self.max_depth
     }
 }
+
+/// An independent empty cache with the same maximum depth and capacity is used by each search thread.
+impl TranspositionTable for Cache {
+    fn get(&self, key: &u64) -> Option<i8> {
+        self.get(key)
+    }
+
+    fn insert(&mut self, key: u64, value: i8) {
+        self.insert(key, value);
+    }
+
+    fn clear(&mut self) {
+        self.clear();
+    }
+
+    fn len(&self) -> usize {
+        self.len()
+    }
+
+    fn fork(&self) -> Self {
+        Self {
+            max_depth: self.max_depth,
+            table: HashMap::new(),
+            capacity: self.capacity,
+        }
+    }
+}
+
+/// A cache associating keys and scores of previously-computed positions that can be shared and modified by multiple threads at once.
+///
+/// The keys are split between a fixed number of shards that are locked independently, so threads rarely wait for each other.
+/// Wrap the cache in an [`Arc`] to use it as the transposition table of an [`Engine`](crate::Engine), which lets parallel searches share the scores they compute.
+///
+/// # Examples
+/// ```
+/// use connect_four_engine::{ConcurrentCache, Engine, Game};
+/// use std::sync::Arc;
+///
+/// let tt_cache = Arc::new(ConcurrentCache::new());
+/// let mut engine = Engine::with_tt_cache(Default::default(), Arc::clone(&tt_cache));
+///
+/// let game = Game::from_str("32164625")?;
+/// assert_eq!(engine.evaluate(&game), 11);
+/// assert!(!tt_cache.is_empty());
+/// # Ok::<(), connect_four_engine::MoveError>(())
+/// ```
+pub struct ConcurrentCache {
+    shards: Box<[Mutex<HashMap<u64, i8>>]>,
+}
+
+impl Default for ConcurrentCache {
+    fn default() -> Self {
+        Self {
+            shards: (0..NUM_SHARDS).map(|_| Mutex::default()).collect(),
+        }
+    }
+}
+
+impl ConcurrentCache {
+    /// Creates an empty cache.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Returns the score of a given position's key or [`None`](Option::None) if the key does not exist in the cache.
+    pub fn get(&self, key: &u64) -> Option<i8> {
+        self.shard(*key).get(key).copied()
+    }
+
+    /// Inserts a position's key and its score as a key-value pair into the cache.
+    pub fn insert(&self, key: u64, value: i8) {
+        self.shard(key).insert(key, value);
+    }
+
+    /// Clears the cache, removing all key-value pairs.
+    pub fn clear(&self) {
+        for shard in self.shards.iter() {
+            lock(shard).clear();
+        }
+    }
+
+    /// Returns `true` if the cache is empty.
+    pub fn is_empty(&self) -> bool {
+        self.shards.iter().all(|shard| lock(shard).is_empty())
+    }
+
+    /// Returns the number of elements in the cache.
+    pub fn len(&self) -> usize {
+        self.shards.iter().map(|shard| lock(shard).len()).sum()
+    }
+
+    /// Locks and returns the shard containing a given key.
+    fn shard(&self, key: u64) -> MutexGuard<'_, HashMap<u64, i8>> {
+        // Mix the bits of the key so that similar positions are spread between shards
+        let index = key.wrapping_mul(0x9E37_79B9_7F4A_7C15) >> (u64::BITS - NUM_SHARDS.ilog2());
+        lock(&self.shards[index as usize])
+    }
+}
+
+/// Locks a shard, ignoring poisoning since a panicking thread cannot leave a map in an invalid state.
+fn lock(shard: &Mutex<HashMap<u64, i8>>) -> MutexGuard<'_, HashMap<u64, i8>> {
+    shard.lock().unwrap_or_else(|e| e.into_inner())
+}
+
+/// Every search thread shares the same cache.
+impl TranspositionTable for Arc<ConcurrentCache> {
+    fn get(&self, key: &u64) -> Option<i8> {
+        ConcurrentCache::get(self, key)
+    }
+
+    fn insert(&mut self, key: u64, value: i8) {
+        ConcurrentCache::insert(self, key, value);
+    }
+
+    fn clear(&mut self) {
+        ConcurrentCache::clear(self);
+    }
+
+    fn len(&self) -> usize {
+        ConcurrentCache::len(self)
+    }
+
+    fn fork(&self) -> Self {
+        Arc::clone(self)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::WIDTH;
+
+    #[test]
+    fn bounded_capacity() {
+        let mut cache = Cache::with_capacity(AREA, 100);
+        for key in 0..1000 {
+            cache.insert(key, 0);
+            assert!(cache.len() <= 100);
+        }
+
+        // Replacing an existing key does not evict anything
+        cache.insert(999, 1);
+        assert_eq!(cache.get(&999), Some(1));
+        assert!(cache.len() > 1);
+
+        assert_eq!(Cache::new(AREA).capacity(), None);
+    }
+
+    #[test]
+    fn concurrent_insert() {
+        let cache = ConcurrentCache::new();
+
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let cache = &cache;
+                scope.spawn(move || {
+                    for key in (thread..1000).step_by(4) {
+                        cache.insert(key, (key % 10) as i8);
+                    }
+                });
+            }
+        });
+
+        assert_eq!(cache.len(), 1000);
+        assert!((0..1000).all(|key| cache.get(&key) == Some((key % 10) as i8)));
+
+        cache.clear();
+        assert!(cache.is_empty());
+    }
+
+    #[test]
+    fn build() {
+        let game = Game::from_str("5554224333234511764415115").unwrap();
+        let board = Board::from(&game);
+        let book = Cache::build_from(&mut Engine::new(), board, 28);
+
+        assert_eq!(book.max_depth(), 28);
+        assert!(!book.is_empty());
+
+        let mut engine = Engine::with_opening_book(book);
+        for first in 0..WIDTH {
+            for second in 0..WIDTH {
+                let mut game = game.clone();
+                if game.play_slice(&[first, second]).is_ok() && !game.is_over() {
+                    assert_eq!(engine.evaluate(&game), Engine::new().evaluate(&game));
+                }
+            }
+        }
+    }
+
+    #[test]
+    #[should_panic(expected = "max_depth is too high")]
+    fn build_too_deep() {
+        Cache::build(&mut Engine::new(), MAX_BOOK_DEPTH + 1);
+    }
+
+    #[test]
+    #[ignore = "too slow"]
+    fn build_book() {
+        let mut engine = Engine::builder().tt_capacity(1 << 23).build();
+        let book = Cache::build(&mut engine, 1);
+        assert_eq!(book.len(), 5);
+    }
+
+    #[test]
+    fn bytes_round_trip() {
+        let mut cache = Cache::new(12);
+        for key in 2..1000 {
+            let score = (key % (MAX_SCORE - MIN_SCORE + 1) as u64) as i8 + MIN_SCORE;
+            cache.insert(key * 7919, score);
+        }
+
+        let bytes = cache.to_bytes();
+        let copy = Cache::from_bytes(bytes.clone()).unwrap();
+
+        assert_eq!(copy.max_depth(), cache.max_depth());
+        assert_eq!(copy.table, cache.table);
+        assert_eq!(copy.to_bytes(), bytes);
+    }
+
+    #[test]
+    fn bytes_empty() {
+        let cache = Cache::new(0);
+        let copy = Cache::from_bytes(cache.to_bytes()).unwrap();
+        assert!(copy.is_empty());
+    }
+
+    #[test]
+    fn bytes_transposition_table() -> Result<(), crate::MoveError> {
+        let mut engine = Engine::new();
+        let game = Game::from_str("32164625")?;
+        engine.evaluate(&game);
+
+        // The keys of a transposition table do not fit in a u32
+        let tt_cache = engine.tt_cache.clone();
+        assert!(tt_cache.table.keys().any(|&key| key > u64::from(u32::MAX)));
+
+        let copy = Cache::from_bytes(tt_cache.to_bytes()).unwrap();
+        assert_eq!(copy.table, tt_cache.table);
+
+        let mut engine = Engine::with_tt_cache(Cache::default(), copy);
+        assert_eq!(engine.evaluate(&game), 11);
+        Ok(())
+    }
+
+    #[test]
+    fn bytes_original_format() {
+        // A maximum depth of 8, then a key of 12345 in the second buffer
+        let mut bytes = vec![8];
+        bytes.extend_from_slice(&BUFFER_DELIMIT.to_le_bytes());
+        bytes.extend_from_slice(&12345u32.to_le_bytes());
+        for _ in MIN_SCORE + 1..=MAX_SCORE {
+            bytes.extend_from_slice(&BUFFER_DELIMIT.to_le_bytes());
+        }
+
+        let cache = Cache::from_bytes(bytes.clone()).unwrap();
+        assert_eq!(cache.max_depth(), 8);
+        assert_eq!(cache.len(), 1);
+        assert_eq!(cache.get(&12345), Some(MIN_SCORE + 1));
+
+        // A missing buffer is invalid in either format
+        bytes.truncate(bytes.len() - 4);
+        assert!(Cache::from_bytes(bytes).is_none());
+        assert!(Cache::from_bytes(vec![BYTES_FORMAT_VERSION, 8]).is_none());
+        assert!(Cache::from_bytes(Vec::new()).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "out of range")]
+    fn bytes_unrepresentable() {
+        let mut cache = Cache::default();
+        cache.insert(2, MAX_SCORE + 1);
+        cache.to_bytes();
+    }
+}
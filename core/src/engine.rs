@@ -8,7 +8,20 @@
 //! * A negative score signifies that the current player will lose.
 //!   * A position has the score of -1 if the player loses with their last piece, -2 if the player loses with their second to last piece, etc.
 
-use crate::{bitboard, Board, Cache, Game, AREA, WIDTH};
+use crate::{
+    bitboard, Board, Cache, Game, MoveError, Outcome, Player, TranspositionTable, AREA,
+    CONNECT_LENGTH, HEIGHT, WIDTH,
+};
+use std::{
+    mem,
+    ops::RangeInclusive,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
 
 /// The minimum possible score of a game position.
 pub const MIN_SCORE: i8 = -MAX_SCORE;
@@ -16,7 +29,17 @@ pub const MIN_SCORE: i8 = -MAX_SCORE;
 /// The maximum possible score of a game position.
 pub const MAX_SCORE: i8 = AREA as i8 / 2 - 3;
 
-/// The reversed column exploration order, starting from the edge columns.
+/// The range of possible scores of a game position, from [`MIN_SCORE`] to [`MAX_SCORE`].
+pub const SCORE_RANGE: RangeInclusive<i8> = MIN_SCORE..=MAX_SCORE;
+
+/// The number of nodes visited between checks of the time limit, avoiding the overhead of reading the clock at every node.
+const TIME_CHECK_INTERVAL: u64 = 4096;
+
+/// The range of the number of random moves played to generate a puzzle position.
+#[cfg(feature = "rand")]
+const PUZZLE_MOVES: RangeInclusive<u8> = 12..=30;
+
+/// The default reversed column exploration order, starting from the edge columns.
 const REV_MOVE_ORDER: [u8; WIDTH as usize] = {
     let mut moves = [0; WIDTH as usize];
     let mut i = 0;
@@ -73,240 +96,2409 @@ impl Iterator for MoveSorter {
 }
 
 /// A solver and analyzer for the game of Connect Four.
-#[derive(Default)]
-pub struct Engine {
+///
+/// The transposition table is a [`Cache`] by default, but any [`TranspositionTable`] can be used, such as an [`Arc<ConcurrentCache>`](crate::ConcurrentCache) shared between threads.
+pub struct Engine<T = Cache> {
     /// The number of nodes visited.
     node_count: u64,
+    /// The maximum number of nodes to visit before aborting the search, if any.
+    node_limit: Option<u64>,
+    /// The time at which the search is aborted, if any.
+    deadline: Option<Instant>,
+    /// Whether the last search was aborted before finding an exact score.
+    aborted: bool,
+    /// Whether any search was aborted during the last evaluation, so that some of its scores are not exact.
+    inexact: bool,
+    /// Whether horizontally mirrored positions share entries in the transposition table.
+    use_symmetry: bool,
+    /// The reversed column exploration order used to break ties between moves with the same number of winning moves.
+    rev_move_order: [u8; WIDTH as usize],
+    /// Whether the score of the last position solved was found in the opening book.
+    from_book: bool,
+    /// The number of positions solved using the opening book.
+    book_hits: u64,
+    /// A flag that aborts the search when set, if any.
+    stop: Option<Arc<AtomicBool>>,
+    /// The search running in the background while pondering, if any.
+    ponder: Option<Ponder<T>>,
     /// An opening book used to cache the scores of opening positions.
     pub opening_book: Cache,
     /// A transposition table used to cache the scores of previously-computed positions.
-    pub tt_cache: Cache,
+    pub tt_cache: T,
+}
+
+impl<T: TranspositionTable + Default> Default for Engine<T> {
+    fn default() -> Self {
+        Self::with_tt_cache(Cache::default(), T::default())
+    }
 }
 
 impl Engine {
     /// Creates a new engine with empty cache.
     pub fn new() -> Self {
-        Self::default()
+        EngineBuilder::new().build()
     }
 
     /// Creates a new engine with an opening book.
     pub fn with_opening_book(opening_book: Cache) -> Self {
-        Self {
-            opening_book,
-            ..Self::default()
-        }
-    }
-
-    /// Returns the number of nodes visited in the last evaluation.
-    pub fn node_count(&self) -> u64 {
-        self.node_count
+        EngineBuilder::new().opening_book(opening_book).build()
     }
 
-    /// Evaluates a game position, returning its score.
+    /// Clamps a computed score into [`SCORE_RANGE`], so scores outside of the range saturate at [`MIN_SCORE`] or [`MAX_SCORE`] instead of wrapping.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Engine, Game};
-    ///
-    /// let game = Game::from_str("32164625")?;
-    /// let mut engine = Engine::new();
+    /// use connect_four_engine::{Engine, MAX_SCORE, MIN_SCORE};
     ///
-    /// let score = engine.evaluate(&game);
-    /// assert_eq!(score, 11);
-    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// assert_eq!(Engine::clamp_score(5), 5);
+    /// assert_eq!(Engine::clamp_score(300), MAX_SCORE);
+    /// assert_eq!(Engine::clamp_score(-300), MIN_SCORE);
     /// ```
-    pub fn evaluate(&mut self, game: &Game) -> i8 {
-        self.node_count = 0;
-        self.solve(game.into())
+    pub fn clamp_score(score: i32) -> i8 {
+        score
+            .clamp(MIN_SCORE.into(), MAX_SCORE.into())
+            .try_into()
+            .unwrap()
     }
 
-    /// Evaluates all the possible moves of a game position, returning the scores as an array.
-    /// An element of the array is [`None`] if the move cannot be played.
+    /// Returns a builder to configure a new engine.
     ///
     /// # Examples
     /// ```
     /// use connect_four_engine::{Engine, Game};
     ///
-    /// let game = Game::from_str("4444413222453233535")?;
-    /// let mut engine = Engine::new();
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::builder().tt_capacity(1 << 16).use_symmetry(true).build();
     ///
-    /// let scores = engine.evaluate_next(&game);
-    /// assert_eq!(scores, [Some(-3), Some(11), Some(-2), None, Some(12), Some(-3), Some(-3)]);
+    /// assert_eq!(engine.evaluate(&game), 11);
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn evaluate_next(&mut self, game: &Game) -> [Option<i8>; WIDTH as usize] {
-        self.node_count = 0;
-
-        let mut scores = [None; WIDTH as usize];
-        let board = Board::from(game);
-
-        for col in 0..WIDTH {
-            if board.is_open(col) {
-                if board.is_winning_move(col) {
-                    scores[col as usize] = Some(board.position_score(true));
-                } else {
-                    let mut new_board = board;
-                    new_board.play_unchecked(col);
-                    scores[col as usize] = Some(-self.solve(new_board));
-                }
-            }
-        }
-
-        scores
+    pub fn builder() -> EngineBuilder {
+        EngineBuilder::new()
     }
+}
 
-    /// Entry function to solve a board.
-    fn solve(&mut self, board: Board) -> i8 {
-        if board.can_win_next() {
-            return board.position_score(true);
-        }
-
-        if board.num_moves() <= self.opening_book.max_depth() {
-            if let Ok(key3) = board.key3().try_into() {
-                if let Some(score) = self.opening_book.get(&key3) {
-                    return score;
-                }
-            }
-        }
+/// A builder to configure and create an [`Engine`].
+#[derive(Default)]
+pub struct EngineBuilder {
+    opening_book: Cache,
+    tt_capacity: Option<usize>,
+    node_limit: Option<u64>,
+    use_symmetry: bool,
+}
 
-        let mut max = board.position_score(false);
-        let mut min = -max;
+impl EngineBuilder {
+    /// Creates a builder with the default configuration: no opening book, an unbounded transposition table, no node limit, and no symmetry.
+    pub fn new() -> Self {
+        Self::default()
+    }
 
-        while min < max {
-            let mut midpoint = min + (max - min) / 2;
-            if midpoint <= 0 && min / 2 < midpoint {
-                midpoint = min / 2;
-            } else if midpoint >= 0 && max / 2 > midpoint {
-                midpoint = max / 2;
-            }
+    /// Sets the opening book used to look up the scores of opening positions.
+    pub fn opening_book(mut self, opening_book: Cache) -> Self {
+        self.opening_book = opening_book;
+        self
+    }
 
-            let score = self.negamax(board, midpoint, midpoint + 1);
+    /// Sets the maximum number of entries in the transposition table.
+    ///
+    /// # Panics
+    /// Panics when building if `capacity` is zero.
+    pub fn tt_capacity(mut self, capacity: usize) -> Self {
+        self.tt_capacity = Some(capacity);
+        self
+    }
 
-            if score <= midpoint {
-                max = score;
-            } else {
-                min = score;
-            }
-        }
-        min
+    /// Sets the maximum number of nodes visited by each evaluation.
+    ///
+    /// If an evaluation exceeds the limit, it is aborted and its score is not exact, as with [`Engine::evaluate_with_budget`].
+    /// Check [`Engine::last_eval_exact`] after evaluating with a limited engine to find out whether the scores can be trusted.
+    /// The limit is replaced by the budget of [`Engine::evaluate_with_budget`] while it runs.
+    pub fn node_limit(mut self, max_nodes: u64) -> Self {
+        self.node_limit = Some(max_nodes);
+        self
     }
 
-    /// Recursively solves a game using the negamax search algorithm, returning its score.
-    fn negamax(&mut self, board: Board, alpha: i8, beta: i8) -> i8 {
-        self.node_count += 1;
+    /// Sets whether horizontally mirrored positions share entries in the transposition table.
+    ///
+    /// Sharing entries lets the search reuse more results, but computing the mirrored key makes each node slower to visit.
+    pub fn use_symmetry(mut self, use_symmetry: bool) -> Self {
+        self.use_symmetry = use_symmetry;
+        self
+    }
 
-        if board.is_full() {
-            return 0;
-        }
+    /// Creates an engine with the configuration of the builder.
+    pub fn build(self) -> Engine {
+        let tt_cache = match self.tt_capacity {
+            Some(capacity) => Cache::with_capacity(AREA, capacity),
+            None => Cache::default(),
+        };
 
-        let non_losing_moves = board.non_losing_moves_bb();
-        if non_losing_moves == 0 {
-            return -board.position_score(false);
+        Engine {
+            node_limit: self.node_limit,
+            use_symmetry: self.use_symmetry,
+            ..Engine::with_tt_cache(self.opening_book, tt_cache)
         }
+    }
+}
 
-        let min = -board.position_score(false) + 1;
-        if min >= beta {
-            return min;
-        }
+/// Where the score of an [`EvalResult`] came from.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EvalSource {
+    /// The score was found in the opening book.
+    Book,
+    /// The score was found by a search that finished.
+    Search,
+    /// The search was aborted by the engine's node limit before finding an exact score.
+    Timeout,
+}
 
-        let max = self.tt_cache.get(&board.key()).unwrap_or(-min + 1);
-        if alpha >= max {
-            return max;
-        }
+/// The score of a game position along with whether it is exact, returned by [`Engine::evaluate_detailed`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct EvalResult {
+    /// The score of the position if it is exact, or otherwise a lower bound of the score.
+    pub score: i8,
+    /// Whether the score is exact, which is `false` only if the search was aborted.
+    pub exact: bool,
+    /// Where the score came from.
+    pub source: EvalSource,
+}
 
-        let mut moves = MoveSorter::new();
+/// A search running in a background thread, started by [`Engine::ponder_start`].
+struct Ponder<T> {
+    /// A flag that aborts the search when set.
+    stop: Arc<AtomicBool>,
+    /// The thread, which returns the transposition table and whether the search finished.
+    handle: JoinHandle<(T, bool)>,
+}
 
-        for col in REV_MOVE_ORDER {
-            let move_board = non_losing_moves & bitboard::column_mask(col);
-            if move_board != 0 {
-                moves.insert(move_board, board.count_winning_moves(move_board));
-            }
-        }
+/// A review of every move of a game, returned by [`Engine::review_game`].
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameReview {
+    /// The player, 0-indexed column, and [`move_loss`](Engine::move_loss) of each move in the order they were played.
+    pub per_move: Vec<(Player, u8, i8)>,
+    /// The accuracy of the first player's moves as a percentage from 0 to 100.
+    pub accuracy_p1: f32,
+    /// The accuracy of the second player's moves as a percentage from 0 to 100.
+    pub accuracy_p2: f32,
+}
 
-        for move_board in moves {
-            let mut new_board = board;
-            new_board.play_bb(move_board);
+impl GameReview {
+    /// Returns the average accuracy of a player's moves, where a move that loses [`MAX_SCORE`] or more has an accuracy of 0.
+    /// A player without any moves has an accuracy of 100.
+    fn accuracy(per_move: &[(Player, u8, i8)], player: Player) -> f32 {
+        let accuracies: Vec<f32> = per_move
+            .iter()
+            .filter(|&&(mover, _, _)| mover == player)
+            .map(|&(_, _, loss)| {
+                100.0 * (1.0 - f32::from(loss.min(MAX_SCORE)) / f32::from(MAX_SCORE))
+            })
+            .collect();
 
-            let score = -self.negamax(new_board, -beta, -alpha);
-            if score >= beta {
-                return score;
-            }
+        if accuracies.is_empty() {
+            100.0
+        } else {
+            accuracies.iter().sum::<f32>() / accuracies.len() as f32
         }
-        self.tt_cache.insert(board.key(), alpha);
-        alpha
     }
 }
 
-#[cfg(test)]
-mod tests {
-    use crate::MoveError;
-
-    use super::*;
-    use std::fs::File;
-    use std::io::{prelude::*, BufReader};
-
-    fn test_file(file_name: &str) {
-        let path = format!("./test_data/{file_name}.csv");
-        let file = File::open(path).unwrap();
-        let reader = BufReader::new(file);
-        let mut engine = Engine::new();
-
-        for line in reader.lines().skip(1) {
-            test_line(line.unwrap(), &mut engine);
+impl<T: TranspositionTable> Engine<T> {
+    /// Creates a new engine with an opening book and a transposition table of any type.
+    pub fn with_tt_cache(opening_book: Cache, tt_cache: T) -> Self {
+        Self {
+            node_count: 0,
+            node_limit: None,
+            deadline: None,
+            aborted: false,
+            inexact: false,
+            use_symmetry: false,
+            rev_move_order: REV_MOVE_ORDER,
+            from_book: false,
+            book_hits: 0,
+            stop: None,
+            ponder: None,
+            opening_book,
+            tt_cache,
         }
     }
 
-    fn test_line(line: String, engine: &mut Engine) {
-        let items: Vec<&str> = line.split(',').collect();
-
-        let [moves, expected] = items[..] else {
-            panic!("file line should have moves and score separated by a comma");
-        };
-
-        let expected: i8 = expected.parse().unwrap();
-        assert_eval(engine, moves, expected);
+    /// Returns the number of nodes visited in the last evaluation.
+    pub fn node_count(&self) -> u64 {
+        self.node_count
     }
 
-    fn assert_eval(engine: &mut Engine, moves: &str, expected: i8) {
-        let game = Game::from_str(moves).expect("move string should be valid");
-        let actual = engine.evaluate(&game);
+    /// Returns the column exploration order, starting from the column explored first.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Engine;
+    ///
+    /// assert_eq!(Engine::new().move_order(), [3, 4, 2, 5, 1, 6, 0]);
+    /// ```
+    pub fn move_order(&self) -> [u8; WIDTH as usize] {
+        let mut order = self.rev_move_order;
+        order.reverse();
+        order
+    }
 
-        assert_eq!(
-            expected, actual,
-            "input = {moves}, expected = {expected}, actual = {actual}"
+    /// Sets the column exploration order, starting from the column explored first. The default order starts from the center column.
+    ///
+    /// Moves are still sorted by their number of winning moves first, so the order only decides between moves with the same number.
+    /// Scores do not depend on the order, but the number of nodes visited does.
+    ///
+    /// # Panics
+    /// Panics if `order` is not a permutation of the 0-indexed columns.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.set_move_order([0, 1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(engine.move_order(), [0, 1, 2, 3, 4, 5, 6]);
+    /// assert_eq!(engine.evaluate(&game), 11);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn set_move_order(&mut self, order: [u8; WIDTH as usize]) {
+        let mut sorted = order;
+        sorted.sort_unstable();
+        assert!(
+            sorted.iter().copied().eq(0..WIDTH),
+            "set_move_order: order is not a permutation of the columns"
         );
+
+        self.rev_move_order = order;
+        self.rev_move_order.reverse();
     }
 
-    #[test]
-    fn begin_easy() {
-        test_file("begin_easy");
+    /// Returns whether the score of the last position solved was found in the opening book instead of by searching.
+    ///
+    /// Methods that solve several positions, such as [`evaluate_next`](Engine::evaluate_next), only report on the last one.
+    pub fn last_eval_from_book(&self) -> bool {
+        self.from_book
     }
 
-    #[test]
-    #[ignore = "too slow"]
-    fn begin_medium() {
-        test_file("begin_medium");
+    /// Returns whether every score returned by the last evaluation is exact.
+    ///
+    /// Scores are only inexact if the engine has a [`node_limit`](EngineBuilder::node_limit) and an evaluation exceeded it,
+    /// in which case the scores returned by methods such as [`evaluate`](Engine::evaluate) and [`evaluate_next`](Engine::evaluate_next) should not be trusted.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::builder().node_limit(10).build();
+    ///
+    /// assert!(engine.evaluate(&game) <= 11);
+    /// assert!(!engine.last_eval_exact());
+    ///
+    /// let mut engine = Engine::new();
+    /// assert_eq!(engine.evaluate(&game), 11);
+    /// assert!(engine.last_eval_exact());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn last_eval_exact(&self) -> bool {
+        !self.inexact
     }
 
-    #[test]
-    #[ignore = "too slow"]
-    fn begin_hard() {
-        test_file("begin_hard");
+    /// Returns the number of positions solved using the opening book since the engine was created or [`reset`](Engine::reset).
+    pub fn book_hits(&self) -> u64 {
+        self.book_hits
     }
 
-    #[test]
-    fn middle_easy() {
-        test_file("middle_easy");
+    /// Clears the transposition table, freeing the scores of previously-computed positions. The opening book is retained.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.evaluate(&game);
+    /// assert!(!engine.tt_cache.is_empty());
+    ///
+    /// engine.clear_cache();
+    /// assert!(engine.tt_cache.is_empty());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn clear_cache(&mut self) {
+        self.tt_cache.clear();
     }
 
-    #[test]
-    fn middle_medium() {
-        test_file("middle_medium");
+    /// Resets the engine to its state before any evaluations by clearing the transposition table, the node count, and the book hits. The opening book is retained.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.evaluate(&game);
+    /// engine.reset();
+    /// assert_eq!(engine.node_count(), 0);
+    /// assert!(engine.tt_cache.is_empty());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn reset(&mut self) {
+        self.clear_cache();
+        self.start_evaluation();
+        self.from_book = false;
+        self.book_hits = 0;
     }
 
-    #[test]
-    fn end_easy() {
-        test_file("end_easy");
+    /// Evaluates a game position, returning its score.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let score = engine.evaluate(&game);
+    /// assert_eq!(score, 11);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate(&mut self, game: &Game) -> i8 {
+        assert_standard(game);
+        self.start_evaluation();
+        self.solve(game.into())
+    }
+
+    /// Evaluates a batch of game positions in order, returning their scores.
+    ///
+    /// The transposition table is shared by every evaluation, so ordering the games by similarity,
+    /// such as grouping positions from the same game, lets later evaluations reuse more of the earlier scores.
+    /// Afterwards, [`node_count`](Engine::node_count) is the total number of nodes visited for the whole batch.
+    ///
+    /// # Panics
+    /// Panics if any game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let games = [Game::from_str("32164625")?, Game::from_str("274552224131661")?];
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.evaluate_batch(&games), vec![11, 0]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_batch(&mut self, games: &[Game]) -> Vec<i8> {
+        games.iter().for_each(assert_standard);
+        self.start_evaluation();
+        games.iter().map(|game| self.solve(game.into())).collect()
+    }
+
+    /// Evaluates a game position twice, first with an empty transposition table and then reusing the table, to check the consistency of the stored scores.
+    /// Only available with the `tt-check` feature, for finding bugs in the transposition table.
+    ///
+    /// The transposition table is cleared before the first evaluation.
+    ///
+    /// # Panics
+    /// Panics if the two scores differ, or if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// # #[cfg(feature = "tt-check")]
+    /// assert_eq!(engine.evaluate_checked(&game), 11);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    #[cfg(feature = "tt-check")]
+    pub fn evaluate_checked(&mut self, game: &Game) -> i8 {
+        self.clear_cache();
+        let fresh = self.evaluate(game);
+        let reused = self.evaluate(game);
+
+        assert_eq!(
+            fresh,
+            reused,
+            "evaluate_checked: scores differ for moves {}, {fresh} with an empty transposition table and {reused} when reused",
+            game.moves_str()
+        );
+        reused
+    }
+
+    /// Evaluates a game position, returning its score along with whether the score is exact and where it came from.
+    ///
+    /// If the engine has a [`node_limit`](EngineBuilder::node_limit) and the search exceeds it, the search is aborted
+    /// and the score is only a lower bound of the actual score, with a source of [`EvalSource::Timeout`].
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, EvalResult, EvalSource, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    ///
+    /// let result = Engine::new().evaluate_detailed(&game);
+    /// assert_eq!(result, EvalResult { score: 11, exact: true, source: EvalSource::Search });
+    ///
+    /// let result = Engine::builder().node_limit(10).build().evaluate_detailed(&game);
+    /// assert!(!result.exact);
+    /// assert_eq!(result.source, EvalSource::Timeout);
+    /// assert!(result.score <= 11);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_detailed(&mut self, game: &Game) -> EvalResult {
+        let score = self.evaluate(game);
+        let exact = self.last_eval_exact();
+        let source = if !exact {
+            EvalSource::Timeout
+        } else if self.from_book {
+            EvalSource::Book
+        } else {
+            EvalSource::Search
+        };
+        EvalResult {
+            score,
+            exact,
+            source,
+        }
+    }
+
+    /// Evaluates a game position, returning its score along with the `(min, max)` bounds of the score that the search narrowed down to find it.
+    ///
+    /// The search repeatedly tests whether the score is above a value between the bounds, moving one of the bounds each time until they meet.
+    /// The first element is the initial bounds, and each following element is the bounds after a test.
+    /// The vector is empty if the score is found without searching, such as when the current player can win immediately or the position is in the opening book.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let (score, bounds) = engine.evaluate_verbose(&game);
+    /// assert_eq!(score, 11);
+    /// assert_eq!(bounds.last(), Some(&(11, 11)));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_verbose(&mut self, game: &Game) -> (i8, Vec<(i8, i8)>) {
+        assert_standard(game);
+        self.start_evaluation();
+
+        let mut bounds = Vec::new();
+        let score = self.solve_traced(game.into(), Some(&mut bounds));
+        (score, bounds)
+    }
+
+    /// Evaluates a game position by taking ownership of the engine and the game, returning the engine along with the score.
+    ///
+    /// This allows an evaluation to be moved into another thread, such as a blocking task of an async runtime,
+    /// and the engine to be reused afterwards with its filled caches, without cloning them.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    /// use std::thread;
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let engine = Engine::new();
+    ///
+    /// let (engine, score) = thread::spawn(move || engine.evaluate_owned(game)).join().unwrap();
+    /// assert_eq!(score, 11);
+    /// assert!(!engine.tt_cache.is_empty());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_owned(mut self, game: Game) -> (Self, i8) {
+        let score = self.evaluate(&game);
+        (self, score)
+    }
+
+    /// Returns the outcome of a game position under perfect play, without the number of moves until the end of the game.
+    ///
+    /// If the game is already over, then its outcome is returned without searching.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game, Outcome, Player};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.outcome_of(&game), Outcome::Win(Player::P1));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn outcome_of(&mut self, game: &Game) -> Outcome {
+        if let Some(outcome) = game.outcome() {
+            return outcome;
+        }
+
+        match self.evaluate(game) {
+            0 => Outcome::Draw,
+            score if score > 0 => Outcome::Win(game.turn()),
+            _ => Outcome::Win(!game.turn()),
+        }
+    }
+
+    /// Checks if the current player should resign because their score is worse than `-threshold`,
+    /// such as when adjudicating engine games without playing out every forced line.
+    ///
+    /// With perfect play, this is exact rather than heuristic: the opponent can always force a win
+    /// at least as quickly as the score implies. A `threshold` of 0 resigns in every lost position.
+    /// Returns `false` if the game is over or the search was aborted by the engine's [`node_limit`](EngineBuilder::node_limit),
+    /// since the score is then not proven.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("321646253")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.evaluate(&game), -11);
+    /// assert!(engine.should_resign(&game, 10));
+    /// assert!(!engine.should_resign(&game, 11));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn should_resign(&mut self, game: &Game, threshold: i8) -> bool {
+        if game.is_over() {
+            return false;
+        }
+
+        let result = self.evaluate_detailed(game);
+        result.exact && result.score < threshold.saturating_neg()
+    }
+
+    /// Checks if the current position is a proven draw, so that either player can claim a draw.
+    ///
+    /// Like [`should_resign`](Engine::should_resign), this is exact with perfect play,
+    /// and returns `false` if the game is over or the search was aborted.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let mut engine = Engine::new();
+    ///
+    /// assert!(engine.should_claim_draw(&Game::from_str("274552224131661")?));
+    /// assert!(!engine.should_claim_draw(&Game::from_str("32164625")?));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn should_claim_draw(&mut self, game: &Game) -> bool {
+        if game.is_over() {
+            return false;
+        }
+
+        let result = self.evaluate_detailed(game);
+        result.exact && result.score == 0
+    }
+
+    /// Returns the number of moves left until the game ends under perfect play, including the winning move if there is one.
+    ///
+    /// The winning player tries to win as early as possible and the losing player tries to lose as late as possible.
+    /// If the game is drawn, then the board is filled, and if the game is already over, then 0 is returned.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.plies_to_end(&game), 13);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn plies_to_end(&mut self, game: &Game) -> u8 {
+        if game.is_over() {
+            return 0;
+        }
+
+        let num_moves = game.num_moves();
+        let score = self.evaluate(game);
+        if score == 0 {
+            return AREA - num_moves;
+        }
+
+        // A win on the move after `win_moves` moves have been played has a score of (AREA + 1 - win_moves) / 2,
+        // and the winner is the current player if the number of moves left before the win is even
+        let mut win_moves = AREA + 1 - 2 * score.unsigned_abs();
+        if (win_moves - num_moves) % 2 != u8::from(score < 0) {
+            win_moves -= 1;
+        }
+        win_moves - num_moves + 1
+    }
+
+    /// Searches for a forced win for the current player within a maximum number of moves, returning the number of moves until the win, including the winning move.
+    ///
+    /// Only wins are proven, so this is much faster than [`evaluate`](Engine::evaluate) for a small `max_plies`.
+    /// The search deepens one move of the current player at a time and stops at the shortest forced win.
+    /// Returns [`None`] if the game is over or the current player cannot force a win within `max_plies` moves.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("4455")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.forced_win_in(&game, 1), None);
+    /// assert_eq!(engine.forced_win_in(&game, 5), Some(3));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn forced_win_in(&mut self, game: &Game, max_plies: u8) -> Option<u8> {
+        assert_standard(game);
+        self.start_evaluation();
+        if game.is_over() {
+            return None;
+        }
+
+        let board = Board::from(game);
+        (1..=max_plies)
+            .step_by(2)
+            .find(|&plies| self.can_force_win(board, plies))
+    }
+
+    /// Evaluates a game position within a budget of visited nodes, returning its score and whether the score is exact.
+    ///
+    /// If the search exceeds `max_nodes`, it is aborted and the returned score is a lower bound of the actual score.
+    /// Aborting does not store any partial results in the transposition table, so later evaluations remain correct.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let (score, exact) = engine.evaluate_with_budget(&game, 10);
+    /// assert!(!exact);
+    /// assert!(score <= 11);
+    ///
+    /// let (score, exact) = engine.evaluate_with_budget(&game, 1_000_000);
+    /// assert!(exact);
+    /// assert_eq!(score, 11);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_with_budget(&mut self, game: &Game, max_nodes: u64) -> (i8, bool) {
+        assert_standard(game);
+        self.start_evaluation();
+        let node_limit = self.node_limit.replace(max_nodes);
+
+        let score = self.solve(game.into());
+        let exact = !self.aborted;
+
+        self.node_limit = node_limit;
+        self.aborted = false;
+        (score, exact)
+    }
+
+    /// Evaluates a game position within a time limit, returning its score or [`None`] if the search did not finish in time.
+    ///
+    /// The time limit is checked every few thousand nodes, so the search may slightly exceed `limit`.
+    /// Aborting does not store any partial results in the transposition table, so later evaluations remain correct.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    /// use std::time::Duration;
+    ///
+    /// let game = Game::from_str("32164625")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let score = engine.evaluate_timed(&game, Duration::from_secs(10));
+    /// assert_eq!(score, Some(11));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_timed(&mut self, game: &Game, limit: Duration) -> Option<i8> {
+        assert_standard(game);
+        self.start_evaluation();
+        self.deadline = Some(Instant::now() + limit);
+
+        let score = self.solve(game.into());
+        let exact = !self.aborted;
+
+        self.deadline = None;
+        self.aborted = false;
+        exact.then_some(score)
+    }
+
+    /// Starts searching the position after an assumed move by the opponent in a background thread, such as while a human opponent is thinking.
+    ///
+    /// The search fills the transposition table, so if the opponent plays the assumed move,
+    /// evaluating or analyzing the resulting position after [`ponder_stop`](Engine::ponder_stop) is much faster.
+    /// Any previous pondering is stopped first, and nothing is searched if the assumed move cannot be played or ends the game.
+    ///
+    /// The transposition table is moved to the background thread until pondering stops, so other evaluations in the meantime use an empty table.
+    /// If the engine is dropped while pondering, the background search stops soon after.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`], or if threads cannot be spawned on the platform.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("5554224333234511764415115")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// engine.ponder_start(&game, 0);
+    /// // ...wait for the opponent's move
+    /// engine.ponder_stop();
+    ///
+    /// let mut game = game;
+    /// game.play(0)?;
+    /// assert_eq!(engine.evaluate(&game), 8);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn ponder_start(&mut self, game: &Game, assumed_move: u8)
+    where
+        T: Default + Send + 'static,
+    {
+        assert_standard(game);
+        self.ponder_stop();
+
+        let mut game = game.clone();
+        if game.play(assumed_move).is_err() || game.is_over() {
+            return;
+        }
+
+        let stop = Arc::new(AtomicBool::new(false));
+        let mut engine = Engine {
+            use_symmetry: self.use_symmetry,
+            rev_move_order: self.rev_move_order,
+            stop: Some(Arc::clone(&stop)),
+            ..Engine::with_tt_cache(self.opening_book.clone(), mem::take(&mut self.tt_cache))
+        };
+        let board = Board::from(&game);
+
+        let handle = thread::spawn(move || {
+            engine.solve(board);
+            (engine.tt_cache, !engine.aborted)
+        });
+        self.ponder = Some(Ponder { stop, handle });
+    }
+
+    /// Stops pondering started by [`ponder_start`](Engine::ponder_start), waiting for the background search to stop and taking back the transposition table.
+    ///
+    /// Returns `true` if the position after the assumed move was completely searched, or `false` if the search was stopped early or the engine was not pondering.
+    /// The scores found by a search that was stopped early are still kept.
+    pub fn ponder_stop(&mut self) -> bool {
+        let Some(ponder) = self.ponder.take() else {
+            return false;
+        };
+
+        ponder.stop.store(true, Ordering::Relaxed);
+        let (tt_cache, finished) = ponder
+            .handle
+            .join()
+            .expect("ponder thread should not panic");
+        self.tt_cache = tt_cache;
+        finished
+    }
+
+    /// Evaluates all the possible moves of a game position, returning the scores as an array.
+    /// An element of the array is [`None`] if the move cannot be played.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("4444413222453233535")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let scores = engine.evaluate_next(&game);
+    /// assert_eq!(scores, [Some(-3), Some(11), Some(-2), None, Some(12), Some(-3), Some(-3)]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_next(&mut self, game: &Game) -> [Option<i8>; WIDTH as usize] {
+        assert_standard(game);
+        self.start_evaluation();
+
+        let mut scores = [None; WIDTH as usize];
+        let board = Board::from(game);
+
+        for col in 0..WIDTH {
+            if board.is_open(col) {
+                scores[col as usize] = Some(self.solve_move(board, col));
+            }
+        }
+
+        scores
+    }
+
+    /// Evaluates the possible moves of a game position one at a time in the order that the search explores them, returning an iterator of each 0-indexed column and its score.
+    ///
+    /// Each move is only evaluated when the iterator is advanced, so the scores of promising moves are available before the rest are computed.
+    /// After the iterator is consumed, the scores are the same as the ones returned by [`evaluate_next`](Engine::evaluate_next).
+    /// If the game is over, then the iterator is empty.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("4444413222453233535")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let mut moves = engine.iter_moves(&game);
+    /// assert_eq!(moves.next(), Some((4, 12)));
+    ///
+    /// let mut scores: Vec<_> = moves.collect();
+    /// scores.sort_unstable();
+    /// assert_eq!(scores, [(0, -3), (1, 11), (2, -2), (5, -3), (6, -3)]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn iter_moves<'a>(&'a mut self, game: &Game) -> impl Iterator<Item = (u8, i8)> + 'a {
+        let moves = self.ordered_moves(game);
+        let board = Board::from(game);
+        self.start_evaluation();
+
+        moves
+            .into_iter()
+            .map(move |col| (col, self.solve_move(board, col)))
+    }
+
+    /// Evaluates all the possible moves of a game position using one thread per move, returning the same scores as [`evaluate_next`](Engine::evaluate_next).
+    ///
+    /// Each thread searches with a clone of the opening book and the transposition table returned by [`TranspositionTable::fork`].
+    /// With a [`Cache`], each thread has its own empty table, so results learned by one thread are not shared with the others and are not stored in [`tt_cache`](Engine::tt_cache).
+    /// With an [`Arc<ConcurrentCache>`](crate::ConcurrentCache), all threads share and fill [`tt_cache`](Engine::tt_cache).
+    /// Afterwards, [`node_count`](Engine::node_count) is the total number of nodes visited across all threads.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("4444413222453233535")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// let scores = engine.evaluate_next_parallel(&game);
+    /// assert_eq!(scores, [Some(-3), Some(11), Some(-2), None, Some(12), Some(-3), Some(-3)]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    #[cfg(feature = "parallel")]
+    pub fn evaluate_next_parallel(&mut self, game: &Game) -> [Option<i8>; WIDTH as usize]
+    where
+        T: Send,
+    {
+        assert_standard(game);
+
+        let mut scores = [None; WIDTH as usize];
+        let board = Board::from(game);
+        let opening_book = &self.opening_book;
+        let (node_limit, use_symmetry, rev_move_order) =
+            (self.node_limit, self.use_symmetry, self.rev_move_order);
+
+        let results: Vec<(u8, i8, u64, bool)> = std::thread::scope(|scope| {
+            let handles: Vec<_> = (0..WIDTH)
+                .filter(|&col| board.is_open(col))
+                .map(|col| {
+                    let tt_cache = self.tt_cache.fork();
+                    scope.spawn(move || {
+                        let mut engine = Engine {
+                            node_limit,
+                            use_symmetry,
+                            rev_move_order,
+                            ..Engine::with_tt_cache(opening_book.clone(), tt_cache)
+                        };
+                        let score = engine.solve_move(board, col);
+                        (col, score, engine.node_count, engine.last_eval_exact())
+                    })
+                })
+                .collect();
+
+            handles
+                .into_iter()
+                .map(|handle| handle.join().expect("search thread should not panic"))
+                .collect()
+        });
+
+        self.start_evaluation();
+        for (col, score, node_count, exact) in results {
+            scores[col as usize] = Some(score);
+            self.node_count += node_count;
+            self.inexact |= !exact;
+        }
+
+        scores
+    }
+
+    /// Evaluates a single move of a game position, returning its score from the perspective of the current player.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the move cannot be played.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game, MoveError};
+    ///
+    /// let game = Game::from_str("4444413222453233535")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.evaluate_move(&game, 4), Ok(12));
+    /// assert_eq!(engine.evaluate_move(&game, 3), Err(MoveError::ColumnFull));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn evaluate_move(&mut self, game: &Game, col: u8) -> Result<i8, MoveError> {
+        assert_standard(game);
+        game.can_play(col)?;
+        self.start_evaluation();
+        Ok(self.solve_move(game.into(), col))
+    }
+
+    /// Returns how much worse a move is than the best move of a game position, from the perspective of the current player.
+    ///
+    /// The loss is the difference between the best score of [`evaluate_next`](Engine::evaluate_next) and the score of the move in the given 0-indexed column,
+    /// so it is 0 if the move is one of the best moves and positive otherwise.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the move cannot be played.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("4444413222453233535")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.move_loss(&game, 4), Ok(0));
+    /// assert_eq!(engine.move_loss(&game, 1), Ok(1));
+    /// assert_eq!(engine.move_loss(&game, 0), Ok(15));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn move_loss(&mut self, game: &Game, col: u8) -> Result<i8, MoveError> {
+        assert_standard(game);
+        game.can_play(col)?;
+
+        let scores = self.evaluate_next(game);
+        let score = scores[col as usize].expect("move_loss: playable move should have a score");
+        let best_score = scores.into_iter().flatten().max().unwrap_or(score);
+
+        Ok(best_score - score)
+    }
+
+    /// Reviews a game by replaying its recorded moves and computing the [`move_loss`](Engine::move_loss) of each one,
+    /// along with the accuracy of each player.
+    ///
+    /// The transposition table is shared across moves, so positions searched for earlier moves speed up the later ones.
+    ///
+    /// # Warning
+    /// Every position before a move is solved, so reviewing a game from an empty board can take a very long time without an opening book.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game, Player};
+    ///
+    /// let mut game = Game::from_matrix(Game::from_str("5554224333234511764415115")?.matrix())?;
+    /// game.play_slice(&[0, 5])?;
+    ///
+    /// let mut engine = Engine::new();
+    /// let review = engine.review_game(&game);
+    ///
+    /// // The second player does not block the threat in the 6th column
+    /// assert_eq!(review.per_move, [(Player::P2, 0, 12), (Player::P1, 5, 0)]);
+    /// assert_eq!(review.accuracy_p1, 100.0);
+    /// assert!(review.accuracy_p2 < 50.0);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn review_game(&mut self, game: &Game) -> GameReview {
+        assert_standard(game);
+
+        let mut position = game.clone();
+        position.undo_n(game.moves().len());
+
+        let per_move: Vec<(Player, u8, i8)> = game
+            .moves()
+            .iter()
+            .map(|&col| {
+                let player = position.turn();
+                let loss = self
+                    .move_loss(&position, col)
+                    .expect("review_game: recorded move should be playable");
+                position
+                    .play(col)
+                    .expect("review_game: recorded move should be playable");
+                (player, col, loss)
+            })
+            .collect();
+
+        GameReview {
+            accuracy_p1: GameReview::accuracy(&per_move, Player::P1),
+            accuracy_p2: GameReview::accuracy(&per_move, Player::P2),
+            per_move,
+        }
+    }
+
+    /// Solves the board after playing a move in the given 0-indexed column, returning its score from the perspective of the player making the move.
+    fn solve_move(&mut self, board: Board, col: u8) -> i8 {
+        if board.is_winning_move(col) {
+            board.position_score(true)
+        } else {
+            let mut new_board = board;
+            new_board.play_unchecked(col);
+            -self.solve(new_board)
+        }
+    }
+
+    /// Solves every unique ongoing position reachable from a game position after a specific number of moves,
+    /// returning an iterator of each position's [`position_key`](Game::position_key) and score.
+    ///
+    /// Positions are solved lazily as the iterator is consumed. Starting from [`Game::new`], this solves every position after `depth` moves,
+    /// which is intended for precomputing scores offline, such as for a tablebase or an opening book.
+    /// Positions are reached from `game` rather than always from the empty board, so that the work can be split up,
+    /// such as by calling this on each position at a shallower depth from a separate process.
+    ///
+    /// # Warning
+    /// The number of positions grows exponentially with the depth (see [`Game::perft`]), and early positions are expensive to solve,
+    /// so this can take a very long time without an opening book.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`], or if the depth is larger than the number of empty tiles.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let mut engine = Engine::new();
+    /// let game = Game::from_str("55542243332345117644151")?;
+    /// let target = Game::from_str("5554224333234511764415115")?;
+    ///
+    /// let position = engine.evaluate_all_at_depth(&game, 2).find(|&(key, _)| key == target.position_key());
+    /// assert_eq!(position, Some((target.position_key(), engine.evaluate(&target))));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn evaluate_all_at_depth(
+        &mut self,
+        game: &Game,
+        depth: u8,
+    ) -> impl Iterator<Item = (u64, i8)> + '_ {
+        assert_standard(game);
+        assert!(
+            depth <= AREA - game.num_moves(),
+            "evaluate_all_at_depth: depth is too high (maximum {})",
+            AREA - game.num_moves()
+        );
+
+        Game::unique_positions(game.into(), depth, |board| board.key().into())
+            .into_iter()
+            .map(move |board| {
+                self.start_evaluation();
+                (board.key(), self.solve(board))
+            })
+    }
+
+    /// Returns the only move of a game position that does not lose, or [`None`] if there are multiple such moves or every move loses.
+    ///
+    /// The forced move may win or draw, while every other move has a negative score.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("5554224333234511764415115")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.forced_move(&game), Some(5));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn forced_move(&mut self, game: &Game) -> Option<u8> {
+        let scores = self.evaluate_next(game);
+        let mut holding_moves =
+            (0..WIDTH).filter(|&col| matches!(scores[col as usize], Some(score) if score >= 0));
+
+        match (holding_moves.next(), holding_moves.next()) {
+            (Some(col), None) => Some(col),
+            _ => None,
+        }
+    }
+
+    /// Returns the 0-indexed columns of the best moves of a game position in ascending order, or an empty vector if the game is over.
+    ///
+    /// If the position is [symmetric](Game::is_symmetric), then a move and its mirror image are equivalent,
+    /// so only the one in the left half of the board is returned.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("52753311433677442422121")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.distinct_best_moves(&game), [4]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn distinct_best_moves(&mut self, game: &Game) -> Vec<u8> {
+        let symmetric = game.is_symmetric();
+        let mut scores: Vec<(u8, i8)> = self
+            .iter_moves(game)
+            .filter(|&(col, _)| !symmetric || col <= WIDTH - 1 - col)
+            .collect();
+        scores.sort_unstable();
+
+        let best_score = scores.iter().map(|&(_, score)| score).max();
+        scores
+            .into_iter()
+            .filter(|&(_, score)| Some(score) == best_score)
+            .map(|(col, _)| col)
+            .collect()
+    }
+
+    /// Returns the playable 0-indexed columns of a game position in the order that the search explores them, without solving the position.
+    ///
+    /// Moves that create more winning opportunities for the current player are explored first, and ties are broken by the [`move_order`](Engine::move_order), which prefers central columns by default.
+    /// If the game is over, then the vector is empty.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let engine = Engine::new();
+    /// assert_eq!(engine.ordered_moves(&Game::new()), vec![3, 4, 2, 5, 1, 6, 0]);
+    /// ```
+    pub fn ordered_moves(&self, game: &Game) -> Vec<u8> {
+        assert_standard(game);
+        if game.is_over() {
+            return Vec::new();
+        }
+
+        let board = Board::from(game);
+        let possible_moves = board.possible_bb();
+        let mut moves = MoveSorter::new();
+
+        for col in self.rev_move_order {
+            let move_board = possible_moves & bitboard::column_mask(col, HEIGHT);
+            if move_board != 0 {
+                moves.insert(move_board, board.count_winning_moves(move_board));
+            }
+        }
+
+        moves
+            .map(|move_board| u8::try_from(move_board.trailing_zeros()).unwrap() / (HEIGHT + 1))
+            .collect()
+    }
+
+    /// Returns the principal variation of a game position, the sequence of 0-indexed columns played by both players under perfect play until the game ends.
+    ///
+    /// The line is derived by repeatedly evaluating the next moves and following a move with the best score, so this is more expensive than [`evaluate`](Engine::evaluate).
+    /// If multiple moves are equally good, any one of them can be chosen.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let game = Game::from_str("112233")?;
+    /// let mut engine = Engine::new();
+    ///
+    /// assert_eq!(engine.principal_variation(&game), vec![3]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn principal_variation(&mut self, game: &Game) -> Vec<u8> {
+        let mut game = game.clone();
+        let mut line = Vec::new();
+
+        while !game.is_over() {
+            let scores = self.evaluate_next(&game);
+            let (col, _) = (0..WIDTH)
+                .filter_map(|col| scores[col as usize].map(|score| (col, score)))
+                .max_by_key(|&(_, score)| score)
+                .expect("ongoing game should have a playable move");
+
+            game.play(col).expect("move should be playable");
+            line.push(col);
+        }
+
+        line
+    }
+
+    /// Returns the best 0-indexed column to play in a game position using only the scores in the opening book, without searching.
+    ///
+    /// Returns [`None`] if the game is over, or if the position or the position after its best move is not in the opening book.
+    /// If multiple moves are equally good, the one explored first by the search is chosen.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Engine, Game};
+    ///
+    /// let engine = Engine::new();
+    /// assert_eq!(engine.book_move(&Game::new()), None);
+    /// ```
+    pub fn book_move(&self, game: &Game) -> Option<u8> {
+        assert_standard(game);
+        if game.is_over() {
+            return None;
+        }
+
+        let board = Board::from(game);
+        let score = self.book_score(&board)?;
+
+        self.ordered_moves(game).into_iter().find(|&col| {
+            let move_score = if board.is_winning_move(col) {
+                Some(board.position_score(true))
+            } else {
+                let mut new_board = board;
+                new_board.play_unchecked(col);
+                self.book_score(&new_board).map(|score| -score)
+            };
+            move_score == Some(score)
+        })
+    }
+
+    /// Returns the scores of all the possible moves of a game position using only the scores in the opening book, without searching,
+    /// in the same format as [`evaluate_next`](Engine::evaluate_next).
+    ///
+    /// An element of the array is [`None`] if the move cannot be played or the position after it is not in the book.
+    /// As in [`book_move`](Engine::book_move), moves that win immediately are scored without the book.
+    /// Returns [`None`] instead of an array if the position is deeper than the book's [`max_depth`](Cache::max_depth).
+    /// At the maximum depth, only moves that win immediately have scores, since the positions after them are not in the book.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Cache, Engine, Game};
+    ///
+    /// let engine = Engine::with_opening_book(Cache::new(4));
+    /// assert_eq!(engine.book_lookup(&Game::from_str("444")?), Some([None; 7]));
+    /// assert_eq!(engine.book_lookup(&Game::from_str("4444")?), Some([None; 7]));
+    /// assert_eq!(engine.book_lookup(&Game::from_str("44445")?), None);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn book_lookup(&self, game: &Game) -> Option<[Option<i8>; WIDTH as usize]> {
+        assert_standard(game);
+        if game.num_moves() > self.opening_book.max_depth() {
+            return None;
+        }
+
+        let board = Board::from(game);
+        let mut scores = [None; WIDTH as usize];
+        if game.is_over() {
+            return Some(scores);
+        }
+
+        for col in (0..WIDTH).filter(|&col| board.is_open(col)) {
+            scores[col as usize] = if board.is_winning_move(col) {
+                Some(board.position_score(true))
+            } else {
+                let mut new_board = board;
+                new_board.play_unchecked(col);
+                self.book_score(&new_board).map(|score| -score)
+            };
+        }
+        Some(scores)
+    }
+
+    /// Returns the score of a board from the opening book, or [`None`] if the board is not in the book.
+    fn book_score(&self, board: &Board) -> Option<i8> {
+        if board.num_moves() > self.opening_book.max_depth() {
+            return None;
+        }
+        self.opening_book.get(&board.key3().try_into().ok()?)
+    }
+
+    /// Resets the node count and whether any search was aborted at the start of an evaluation.
+    fn start_evaluation(&mut self) {
+        self.node_count = 0;
+        self.inexact = false;
+    }
+
+    /// Entry function to solve a board.
+    pub(crate) fn solve(&mut self, board: Board) -> i8 {
+        self.solve_traced(board, None)
+    }
+
+    /// Solves a board, recording the bounds of the score before the search and after each null-window search if `bounds` is given.
+    fn solve_traced(&mut self, board: Board, mut bounds: Option<&mut Vec<(i8, i8)>>) -> i8 {
+        self.aborted = false;
+        self.from_book = false;
+        if board.can_win_next() {
+            return board.position_score(true);
+        }
+
+        if let Some(score) = self.book_score(&board) {
+            self.from_book = true;
+            self.book_hits += 1;
+            return score;
+        }
+
+        let mut max = Engine::clamp_score(board.position_score(false).into());
+        let mut min = -max;
+        if let Some(bounds) = bounds.as_deref_mut() {
+            bounds.push((min, max));
+        }
+
+        while min < max {
+            let mut midpoint = min + (max - min) / 2;
+            if midpoint <= 0 && min / 2 < midpoint {
+                midpoint = min / 2;
+            } else if midpoint >= 0 && max / 2 > midpoint {
+                midpoint = max / 2;
+            }
+
+            let score = self.negamax(board, midpoint, midpoint + 1);
+            if self.aborted {
+                break;
+            }
+
+            if score <= midpoint {
+                max = score;
+            } else {
+                min = score;
+            }
+            if let Some(bounds) = bounds.as_deref_mut() {
+                bounds.push((min, max));
+            }
+        }
+
+        self.inexact |= self.aborted;
+        min
+    }
+
+    /// Recursively solves a game using the negamax search algorithm, returning its score.
+    /// If the search is aborted, the returned score is meaningless and nothing is stored in the transposition table.
+    fn negamax(&mut self, board: Board, alpha: i8, beta: i8) -> i8 {
+        self.node_count += 1;
+
+        if self.should_abort() {
+            self.aborted = true;
+            return 0;
+        }
+
+        if board.is_full() {
+            return 0;
+        }
+
+        let non_losing_moves = board.non_losing_moves_bb();
+        if non_losing_moves == 0 {
+            return -board.position_score(false);
+        }
+
+        let min = -board.position_score(false) + 1;
+        if min >= beta {
+            return min;
+        }
+
+        let max = self.tt_cache.get(&self.tt_key(&board)).unwrap_or(-min + 1);
+        if alpha >= max {
+            return max;
+        }
+
+        let mut moves = MoveSorter::new();
+
+        for col in self.rev_move_order {
+            let move_board = non_losing_moves & bitboard::column_mask(col, HEIGHT);
+            if move_board != 0 {
+                moves.insert(move_board, board.count_winning_moves(move_board));
+            }
+        }
+
+        for move_board in moves {
+            let mut new_board = board;
+            new_board.play_bb(move_board);
+
+            let score = -self.negamax(new_board, -beta, -alpha);
+            if self.aborted {
+                return alpha;
+            }
+            if score >= beta {
+                return score;
+            }
+        }
+        self.tt_cache.insert(self.tt_key(&board), alpha);
+        alpha
+    }
+
+    /// Returns the key of a board in the transposition table.
+    #[inline]
+    fn tt_key(&self, board: &Board) -> u64 {
+        if self.use_symmetry {
+            board.key().min(board.mirror().key())
+        } else {
+            board.key()
+        }
+    }
+
+    /// Generates a puzzle position by playing random moves, where the best move is better than every other move by at least `min_advantage`.
+    ///
+    /// Random positions are generated and evaluated with [`evaluate_next`](Engine::evaluate_next) until one has a single best move.
+    /// The difficulty scales with `min_advantage`: a large advantage makes the best move easier to find, such as a move that wins or avoids losing,
+    /// while an advantage of 1 can mean that the best move only wins more quickly or loses more slowly than the other moves.
+    ///
+    /// # Panics
+    /// Panics if `min_advantage` is not positive.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Engine;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(4);
+    /// let mut engine = Engine::new();
+    ///
+    /// let puzzle = engine.generate_puzzle(&mut rng, 10);
+    /// let mut scores: Vec<i8> = engine.evaluate_next(&puzzle).into_iter().flatten().collect();
+    /// scores.sort_unstable_by(|a, b| b.cmp(a));
+    ///
+    /// assert!(scores[0] - scores[1] >= 10);
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn generate_puzzle<R: rand::Rng + ?Sized>(
+        &mut self,
+        rng: &mut R,
+        min_advantage: i8,
+    ) -> Game {
+        assert!(
+            min_advantage > 0,
+            "generate_puzzle: min_advantage must be positive"
+        );
+
+        loop {
+            let mut game = Game::new();
+            let num_moves = rng.gen_range(PUZZLE_MOVES);
+            while game.num_moves() < num_moves && game.play_random(rng).is_some() {}
+            if game.is_over() {
+                continue;
+            }
+
+            let mut scores: Vec<i8> = self.evaluate_next(&game).into_iter().flatten().collect();
+            scores.sort_unstable_by(|a, b| b.cmp(a));
+            if let [best, second, ..] = scores[..] {
+                if best - second >= min_advantage {
+                    return game;
+                }
+            }
+        }
+    }
+
+    /// Recursively checks whether the current player can force a win within a number of moves, including the winning move.
+    fn can_force_win(&mut self, board: Board, plies: u8) -> bool {
+        self.node_count += 1;
+
+        if board.can_win_next() {
+            return true;
+        }
+        if plies < 3 {
+            return false;
+        }
+
+        let non_losing_moves = board.non_losing_moves_bb();
+        'moves: for col in self.rev_move_order {
+            let move_board = non_losing_moves & bitboard::column_mask(col, HEIGHT);
+            if move_board == 0 {
+                continue;
+            }
+
+            let mut reply_board = board;
+            reply_board.play_bb(move_board);
+            if reply_board.is_full() {
+                continue;
+            }
+
+            // Every reply of the opponent must still lead to a forced win
+            let replies = reply_board.possible_bb();
+            for reply_col in 0..WIDTH {
+                let reply = replies & bitboard::column_mask(reply_col, HEIGHT);
+                if reply == 0 {
+                    continue;
+                }
+
+                let mut new_board = reply_board;
+                new_board.play_bb(reply);
+                if !self.can_force_win(new_board, plies - 2) {
+                    continue 'moves;
+                }
+            }
+            return true;
+        }
+        false
+    }
+
+    /// Checks whether the current search has exceeded its node limit or time limit.
+    #[inline]
+    fn should_abort(&self) -> bool {
+        if matches!(self.node_limit, Some(limit) if self.node_count > limit) {
+            return true;
+        }
+        if !self.node_count.is_multiple_of(TIME_CHECK_INTERVAL) {
+            return false;
+        }
+
+        // The search is also stopped if the engine that started pondering has been dropped
+        let stopped = self
+            .stop
+            .as_ref()
+            .is_some_and(|stop| stop.load(Ordering::Relaxed) || Arc::strong_count(stop) == 1);
+        stopped
+            || self
+                .deadline
+                .is_some_and(|deadline| Instant::now() >= deadline)
+    }
+}
+
+/// Asserts that a game has the standard dimensions supported by the solver.
+fn assert_standard(game: &Game) {
+    assert!(
+        game.width() == WIDTH && game.height() == HEIGHT,
+        "engine only supports games with standard dimensions ({WIDTH}x{HEIGHT})"
+    );
+    assert!(
+        game.connect_length() == CONNECT_LENGTH,
+        "engine only supports games with the standard connect length ({CONNECT_LENGTH})"
+    );
+    assert!(
+        game.board.num_pieces() == game.num_moves(),
+        "engine does not support games with pop outs"
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::Player;
+
+    use super::*;
+    use std::fs::File;
+    use std::io::{prelude::*, BufReader};
+
+    fn test_file(file_name: &str) {
+        let path = format!("./test_data/{file_name}.csv");
+        let file = File::open(path).unwrap();
+        let reader = BufReader::new(file);
+        let mut engine = Engine::new();
+
+        for line in reader.lines().skip(1) {
+            test_line(line.unwrap(), &mut engine);
+        }
+    }
+
+    fn test_line(line: String, engine: &mut Engine) {
+        let items: Vec<&str> = line.split(',').collect();
+
+        let [moves, expected] = items[..] else {
+            panic!("file line should have moves and score separated by a comma");
+        };
+
+        let expected: i8 = expected.parse().unwrap();
+        assert_eval(engine, moves, expected);
+    }
+
+    #[cfg(feature = "tt-check")]
+    fn test_file_checked(file_name: &str, num_lines: usize) {
+        let file = File::open(format!("./test_data/{file_name}.csv")).unwrap();
+        let mut engine = Engine::new();
+
+        for line in BufReader::new(file).lines().skip(1).take(num_lines) {
+            let line = line.unwrap();
+            let (moves, expected) = line.split_once(',').unwrap();
+
+            let game = Game::from_str(moves).unwrap();
+            assert_eq!(engine.evaluate_checked(&game), expected.parse().unwrap());
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "tt-check")]
+    fn evaluate_checked() {
+        test_file_checked("middle_easy", 20);
+    }
+
+    #[test]
+    #[cfg(feature = "tt-check")]
+    #[ignore = "too slow"]
+    fn evaluate_checked_begin_hard() {
+        test_file_checked("begin_hard", 1000);
+    }
+
+    fn assert_eval(engine: &mut Engine, moves: &str, expected: i8) {
+        let game = Game::from_str(moves).expect("move string should be valid");
+        let actual = engine.evaluate(&game);
+
+        assert_eq!(
+            expected, actual,
+            "input = {moves}, expected = {expected}, actual = {actual}"
+        );
+    }
+
+    #[test]
+    fn begin_easy() {
+        test_file("begin_easy");
+    }
+
+    #[test]
+    #[ignore = "too slow"]
+    fn begin_medium() {
+        test_file("begin_medium");
+    }
+
+    #[test]
+    #[ignore = "too slow"]
+    fn begin_hard() {
+        test_file("begin_hard");
+    }
+
+    #[test]
+    fn middle_easy() {
+        test_file("middle_easy");
+    }
+
+    #[test]
+    fn middle_medium() {
+        test_file("middle_medium");
+    }
+
+    #[test]
+    fn end_easy() {
+        test_file("end_easy");
+    }
+
+    #[test]
+    fn node_budget() -> Result<(), MoveError> {
+        let game = Game::from_str("274552224131661")?;
+        let mut engine = Engine::new();
+
+        let (score, exact) = engine.evaluate_with_budget(&game, 1);
+        assert!(!exact);
+        assert!(score <= 0);
+
+        for max_nodes in [10, 1_000, 100_000] {
+            let (score, exact) = engine.evaluate_with_budget(&game, max_nodes);
+            assert!(score <= 0);
+            assert!(!exact || score == 0);
+        }
+
+        assert_eq!(engine.evaluate(&game), 0);
+        assert_eq!(engine.evaluate_with_budget(&game, u64::MAX), (0, true));
+        Ok(())
+    }
+
+    #[test]
+    fn time_limit() -> Result<(), MoveError> {
+        let game = Game::new();
+        let mut engine = Engine::new();
+
+        assert_eq!(engine.evaluate_timed(&game, Duration::ZERO), None);
+
+        let game = Game::from_str("274552224131661")?;
+        assert_eq!(
+            engine.evaluate_timed(&game, Duration::from_secs(60)),
+            Some(0)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn bounded_cache() {
+        let mut engine = Engine::new();
+        engine.tt_cache = Cache::with_capacity(AREA, 1000);
+
+        for (moves, expected) in [("274552224131661", 0), ("32164625", 11)] {
+            assert_eval(&mut engine, moves, expected);
+            assert!(engine.tt_cache.len() <= 1000);
+        }
+    }
+
+    #[test]
+    fn ordered_moves() -> Result<(), MoveError> {
+        let engine = Engine::new();
+
+        // _ _ _ _ _ _ _
+        // _ O O _ _ _ _
+        // _ X X _ _ _ _
+        let game = Game::from_str("2233")?;
+        assert_eq!(engine.ordered_moves(&game), vec![3, 4, 0, 2, 5, 1, 6]);
+
+        let game = Game::from_str("111111222222333333544444455555666666777777")?;
+        assert!(engine.ordered_moves(&game).is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn set_move_order() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let mut engine = Engine::new();
+        let score = engine.evaluate(&game);
+        let node_count = engine.node_count();
+
+        let mut engine = Engine::new();
+        engine.set_move_order([0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(engine.move_order(), [0, 1, 2, 3, 4, 5, 6]);
+        assert_eq!(engine.evaluate(&game), score);
+        assert_ne!(engine.node_count(), node_count);
+        assert_eq!(
+            engine.ordered_moves(&Game::new()),
+            vec![0, 1, 2, 3, 4, 5, 6]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn default_move_order() {
+        assert_eq!(
+            Engine::<Cache>::default().move_order(),
+            Engine::new().move_order()
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "permutation")]
+    fn set_move_order_invalid() {
+        Engine::new().set_move_order([0, 1, 2, 3, 3, 5, 6]);
+    }
+
+    #[test]
+    #[should_panic(expected = "pop outs")]
+    fn evaluate_pop_out() {
+        let mut game = Game::from_str("4455").unwrap();
+        game.pop_out(3).unwrap();
+        Engine::new().evaluate(&game);
+    }
+
+    #[test]
+    fn principal_variation() -> Result<(), MoveError> {
+        use crate::Status;
+
+        let mut engine = Engine::new();
+
+        // P1 wins with their 11th piece (the 21st move overall)
+        let mut game = Game::from_str("32164625")?;
+        let line = engine.principal_variation(&game);
+        assert_eq!(line.len(), 13);
+
+        game.play_slice(&line)?;
+        assert_eq!(game.status(), Status::Win(Player::P1));
+
+        // Draws are played until the board is full
+        let mut game = Game::from_str("274552224131661")?;
+        let line = engine.principal_variation(&game);
+        assert_eq!(
+            usize::from(game.num_moves()) + line.len(),
+            usize::from(AREA)
+        );
+
+        game.play_slice(&line)?;
+        assert_eq!(game.status(), Status::Draw);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_next() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        for moves in [
+            "5554224333234511764415115",
+            "52753311433677442422121",
+            "4444413222453233535",
+        ] {
+            let game = Game::from_str(moves)?;
+            let expected = engine.evaluate_next(&game);
+            assert_eq!(engine.evaluate_next_parallel(&game), expected);
+            assert!(engine.node_count() > 0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn concurrent_cache() -> Result<(), MoveError> {
+        use crate::ConcurrentCache;
+        use std::sync::Arc;
+
+        let mut engine = Engine::with_tt_cache(Cache::default(), Arc::new(ConcurrentCache::new()));
+        for (moves, expected) in [("274552224131661", 0), ("32164625", 11)] {
+            let game = Game::from_str(moves)?;
+            assert_eq!(engine.evaluate(&game), expected);
+        }
+        assert!(!engine.tt_cache.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "parallel")]
+    fn parallel_next_shared() -> Result<(), MoveError> {
+        use crate::ConcurrentCache;
+        use std::sync::Arc;
+
+        let tt_cache = Arc::new(ConcurrentCache::new());
+        let mut engine = Engine::with_tt_cache(Cache::default(), Arc::clone(&tt_cache));
+
+        for moves in [
+            "5554224333234511764415115",
+            "52753311433677442422121",
+            "4444413222453233535",
+        ] {
+            let game = Game::from_str(moves)?;
+            let expected = Engine::new().evaluate_next(&game);
+            assert_eq!(engine.evaluate_next_parallel(&game), expected);
+        }
+        assert!(!tt_cache.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn book_move() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let board = Board::from(&game);
+        let mut engine = Engine::new();
+
+        let mut book = Cache::new(board.num_moves() + 1);
+        book.insert(board.key3().try_into().unwrap(), engine.evaluate(&game));
+        assert_eq!(
+            Engine::with_opening_book(book.clone()).book_move(&game),
+            None
+        );
+
+        for col in (0..WIDTH).filter(|&col| game.can_play(col).is_ok()) {
+            let mut new_game = game.clone();
+            new_game.play(col)?;
+            if !new_game.is_over() {
+                let key3 = Board::from(&new_game).key3().try_into().unwrap();
+                book.insert(key3, engine.evaluate(&new_game));
+            }
+        }
+
+        let scores = engine.evaluate_next(&game);
+        let best = scores.iter().flatten().max().copied();
+        let col = Engine::with_opening_book(book).book_move(&game).unwrap();
+        assert_eq!(scores[col as usize], best);
+        Ok(())
+    }
+
+    #[test]
+    fn book_lookup() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let mut engine = Engine::new();
+        let mut book = Cache::new(game.num_moves() + 1);
+
+        let mut missing = None;
+        for col in (0..WIDTH).filter(|&col| game.can_play(col).is_ok()) {
+            let mut new_game = game.clone();
+            new_game.play(col)?;
+            if missing.is_none() {
+                missing = Some(col);
+            } else {
+                let key3 = Board::from(&new_game).key3().try_into().unwrap();
+                book.insert(key3, engine.evaluate(&new_game));
+            }
+        }
+
+        let mut expected = engine.evaluate_next(&game);
+        expected[usize::from(missing.unwrap())] = None;
+
+        let engine = Engine::with_opening_book(book);
+        assert_eq!(engine.book_lookup(&game), Some(expected));
+
+        // At the maximum depth, only the immediate win in the sixth column is scored
+        let game = Game::from_str("55542243332345117644151151")?;
+        let mut expected = [None; WIDTH as usize];
+        expected[5] = Some(8);
+        assert_eq!(engine.book_lookup(&game), Some(expected));
+        assert_eq!(
+            engine.book_lookup(&Game::from_str("555422433323451176441511514")?),
+            None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn book_hits() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let board = Board::from(&game);
+        let score = Engine::new().evaluate(&game);
+
+        let mut book = Cache::new(board.num_moves());
+        book.insert(board.key3().try_into().unwrap(), score);
+        let mut engine = Engine::with_opening_book(book);
+
+        assert_eq!(engine.evaluate(&game), score);
+        assert!(engine.last_eval_from_book());
+        assert_eq!(engine.node_count(), 0);
+
+        let mut new_game = game.clone();
+        new_game.play(0)?;
+        engine.evaluate(&new_game);
+        assert!(!engine.last_eval_from_book());
+
+        assert_eq!(
+            engine.evaluate(&Game::from_str("3334664555654377124473773")?),
+            score
+        );
+        assert!(engine.last_eval_from_book());
+        assert_eq!(engine.book_hits(), 2);
+
+        engine.reset();
+        assert!(!engine.last_eval_from_book());
+        assert_eq!(engine.book_hits(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn outcome_of() -> Result<(), MoveError> {
+        use crate::Player;
+
+        let mut engine = Engine::new();
+        for (moves, expected) in [
+            ("32164625", Outcome::Win(Player::P1)),
+            ("52753311433677442422121", Outcome::Win(Player::P2)),
+            ("274552224131661", Outcome::Draw),
+            ("4455667", Outcome::Win(Player::P1)),
+        ] {
+            assert_eq!(engine.outcome_of(&Game::from_str(moves)?), expected);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn builder() -> Result<(), MoveError> {
+        let game = Game::from_str("274552224131661")?;
+
+        let mut engine = Engine::builder()
+            .tt_capacity(1000)
+            .use_symmetry(true)
+            .build();
+        assert_eq!(engine.evaluate(&game), 0);
+        assert!(engine.tt_cache.len() <= 1000);
+        assert_eq!(engine.tt_cache.capacity(), Some(1000));
+
+        let mut engine = Engine::builder().node_limit(10).build();
+        assert!(engine.evaluate(&game) <= 0);
+        assert!(engine.node_count() <= 11);
+        assert_eq!(engine.evaluate_with_budget(&game, 1_000_000), (0, true));
+        assert!(engine.last_eval_exact());
+
+        let game = Game::from_str("32164625")?;
+        assert!(engine.evaluate(&game) <= 11);
+        assert!(engine.node_count() <= 11);
+        assert!(!engine.last_eval_exact());
+
+        let mut engine = Engine::builder().node_limit(10).build();
+        engine.evaluate_next(&game);
+        assert!(!engine.last_eval_exact());
+        Ok(())
+    }
+
+    #[test]
+    fn plies_to_end() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        let game = Game::from_str("274552224131661")?;
+        assert_eq!(engine.plies_to_end(&game), 27);
+
+        // The game ends on a move of the winning player
+        for moves in ["32164625", "321646251", "5554224333234511764415115"] {
+            let game = Game::from_str(moves)?;
+            let plies = engine.plies_to_end(&game);
+            let score = engine.evaluate(&game);
+            assert_eq!(plies % 2 == 1, score > 0);
+            assert!(game.num_moves() + plies <= AREA);
+        }
+
+        let game = Game::from_str("4455667")?;
+        assert_eq!(engine.plies_to_end(&game), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn forced_win_in() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        let game = Game::from_str("32164625")?;
+        assert_eq!(engine.forced_win_in(&game, 11), None);
+        assert_eq!(engine.forced_win_in(&game, 13), Some(13));
+
+        let game = Game::from_str("4455667")?;
+        assert_eq!(engine.forced_win_in(&game, 7), None);
+        Ok(())
+    }
+
+    #[test]
+    #[cfg(feature = "rand")]
+    fn generate_puzzle() {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut engine = Engine::new();
+
+        for min_advantage in [1, 5, 20] {
+            let puzzle = engine.generate_puzzle(&mut rng, min_advantage);
+            assert!(!puzzle.is_over());
+
+            let scores = engine.evaluate_next(&puzzle);
+            let best = scores.iter().flatten().max().unwrap();
+            assert!(scores
+                .iter()
+                .flatten()
+                .filter(|&score| score != best)
+                .all(|score| best - score >= min_advantage));
+            assert_eq!(
+                scores
+                    .iter()
+                    .flatten()
+                    .filter(|&score| score == best)
+                    .count(),
+                1
+            );
+        }
+    }
+
+    #[test]
+    fn reset() -> Result<(), MoveError> {
+        let mut book = Cache::new(2);
+        book.insert(0, 1);
+        let mut engine = Engine::with_opening_book(book);
+
+        engine.evaluate(&Game::from_str("32164625")?);
+        engine.reset();
+
+        assert_eq!(engine.node_count(), 0);
+        assert!(engine.tt_cache.is_empty());
+        assert_eq!(engine.opening_book.get(&0), Some(1));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_all_at_depth() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+        let game = Game::from_str("5554224333234511764415115")?;
+
+        let positions: Vec<_> = engine.evaluate_all_at_depth(&game, 2).collect();
+        let boards = Game::unique_positions((&game).into(), 2, |board| board.key().into());
+        assert_eq!(positions.len(), boards.len());
+
+        for (board, (key, score)) in boards.into_iter().zip(positions) {
+            assert!(!board.is_terminal());
+            assert_eq!(board.key(), key);
+            assert_eq!(Engine::new().solve(board), score);
+        }
+
+        let positions = Game::unique_positions(Board::new(), 4, |board| board.key().into());
+        assert_eq!(positions.len() as u64, Game::perft(4));
+        Ok(())
+    }
+
+    #[test]
+    fn iter_moves() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+        for moves in [
+            "5554224333234511764415115",
+            "52753311433677442422121",
+            "4455667",
+        ] {
+            let game = Game::from_str(moves)?;
+            let expected = engine.evaluate_next(&game);
+            let order = engine.ordered_moves(&game);
+
+            let mut scores = [None; WIDTH as usize];
+            let mut cols = Vec::new();
+            for (col, score) in engine.iter_moves(&game) {
+                scores[col as usize] = Some(score);
+                cols.push(col);
+            }
+
+            assert_eq!(cols, order);
+            if !game.is_over() {
+                assert_eq!(scores, expected);
+            }
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn forced_move() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        // The opponent threatens to win in the 6th column, which is the only move that does not lose
+        let game = Game::from_str("5554224333234511764415115")?;
+        assert_eq!(engine.forced_move(&game), Some(5));
+
+        // Many moves win
+        let game = Game::from_str("52753311433677442422121")?;
+        assert_eq!(engine.forced_move(&game), None);
+
+        // Every move loses
+        let game = Game::from_str("5275331143367744242212")?;
+        assert_eq!(engine.forced_move(&game), None);
+        Ok(())
+    }
+
+    #[test]
+    fn review_game() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        // Only the moves played after the position was set up are reviewed
+        let mut game = Game::from_matrix(Game::from_str("55542243332345117644151")?.matrix())?;
+        game.play_slice(&[0, 4, 0, 5])?;
+
+        let review = engine.review_game(&game);
+        assert_eq!(
+            review.per_move,
+            [
+                (Player::P2, 0, 13),
+                (Player::P1, 4, 13),
+                (Player::P2, 0, 12),
+                (Player::P1, 5, 0)
+            ]
+        );
+        assert!((review.accuracy_p1 - 575.0 / 9.0).abs() < 1e-3);
+        assert!((review.accuracy_p2 - 275.0 / 9.0).abs() < 1e-3);
+
+        let review = engine.review_game(&Game::new());
+        assert!(review.per_move.is_empty());
+        assert_eq!((review.accuracy_p1, review.accuracy_p2), (100.0, 100.0));
+        Ok(())
+    }
+
+    #[test]
+    fn clamp_score() {
+        assert_eq!(Engine::clamp_score(0), 0);
+        assert_eq!(Engine::clamp_score(MIN_SCORE.into()), MIN_SCORE);
+        assert_eq!(Engine::clamp_score(MAX_SCORE.into()), MAX_SCORE);
+        assert_eq!(Engine::clamp_score(i32::from(MAX_SCORE) + 1), MAX_SCORE);
+
+        // Values that wrap to valid scores when truncated to 8 bits
+        assert_eq!(Engine::clamp_score(256 + 5), MAX_SCORE);
+        assert_eq!(Engine::clamp_score(-256 - 5), MIN_SCORE);
+        assert_eq!(Engine::clamp_score(i32::MAX), MAX_SCORE);
+        assert_eq!(Engine::clamp_score(i32::MIN), MIN_SCORE);
+        assert!(SCORE_RANGE.contains(&Engine::clamp_score(AREA.into())));
+    }
+
+    #[test]
+    fn distinct_best_moves() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        // The best moves in the 3rd and 5th columns are mirror images
+        let game = Game::from_str("632577111672325661275137")?;
+        assert!(game.is_symmetric());
+        assert_eq!(engine.distinct_best_moves(&game), [2]);
+
+        let game = Game::from_str("256337515236256323653157")?;
+        assert_eq!(engine.distinct_best_moves(&game), [1, 3]);
+
+        let game = Game::from_str("4444413222453233535")?;
+        assert!(!game.is_symmetric());
+        assert_eq!(engine.distinct_best_moves(&game), [4]);
+
+        assert!(engine
+            .distinct_best_moves(&Game::from_str("1212121")?)
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_detailed() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let board = Board::from(&game);
+        let score = Engine::new().evaluate(&game);
+
+        let mut book = Cache::new(board.num_moves());
+        book.insert(board.key3().try_into().unwrap(), score);
+        let mut engine = Engine::with_opening_book(book);
+
+        let result = engine.evaluate_detailed(&game);
+        assert_eq!(
+            result,
+            EvalResult {
+                score,
+                exact: true,
+                source: EvalSource::Book
+            }
+        );
+
+        // Positions deeper than the book are searched
+        let mut new_game = game.clone();
+        new_game.play(0)?;
+        assert_eq!(
+            engine.evaluate_detailed(&new_game).source,
+            EvalSource::Search
+        );
+
+        let mut engine = Engine::builder().node_limit(1).build();
+        let result = engine.evaluate_detailed(&game);
+        assert_eq!(result.source, EvalSource::Timeout);
+        assert!(!result.exact);
+        assert!(result.score <= score);
+        Ok(())
+    }
+
+    #[test]
+    fn resign_and_claim_draw() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+        let lost = Game::from_str("321646253")?;
+        assert!(engine.should_resign(&lost, 0));
+        assert!(engine.should_resign(&lost, i8::MIN));
+        assert!(!engine.should_claim_draw(&lost));
+
+        // Finished games are never adjudicated
+        let over = Game::from_str("4455667")?;
+        assert!(!engine.should_resign(&over, 0));
+        assert!(!engine.should_claim_draw(&over));
+
+        // An aborted search proves nothing
+        let mut engine = Engine::builder().node_limit(1).build();
+        assert!(!engine.should_resign(&lost, 0));
+        assert!(!engine.should_claim_draw(&Game::from_str("274552224131661")?));
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_batch() -> Result<(), MoveError> {
+        let games = [
+            Game::from_str("5554224333234511764415115")?,
+            Game::from_str("55542243332345117644151151")?,
+            Game::from_str("32164625")?,
+        ];
+
+        let mut engine = Engine::new();
+        let mut expected = Vec::new();
+        let mut total_nodes = 0;
+        for game in &games {
+            expected.push(engine.evaluate(game));
+            total_nodes += engine.node_count();
+        }
+
+        let mut engine = Engine::new();
+        assert_eq!(engine.evaluate_batch(&games), expected);
+        assert_eq!(engine.node_count(), total_nodes);
+        assert!(engine.evaluate_batch(&[]).is_empty());
+        assert_eq!(engine.node_count(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn ponder() -> Result<(), MoveError> {
+        let game = Game::from_str("5554224333234511764415115")?;
+        let mut child = game.clone();
+        child.play(5)?;
+
+        let mut fresh_engine = Engine::new();
+        let score = fresh_engine.evaluate(&child);
+
+        let mut engine = Engine::new();
+        engine.ponder_start(&game, 5);
+        while !engine.ponder.as_ref().unwrap().handle.is_finished() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        assert!(engine.ponder_stop());
+        assert!(!engine.tt_cache.is_empty());
+
+        // The pondered position is answered from the transposition table
+        assert_eq!(engine.evaluate(&child), score);
+        assert!(engine.node_count() < fresh_engine.node_count());
+        assert!(!engine.ponder_stop());
+
+        // Pondering is stopped long before the empty board is solved
+        engine.ponder_start(&Game::new(), 3);
+        assert!(!engine.ponder_stop());
+
+        // Nothing is searched for a move that cannot be played
+        engine.ponder_start(&Game::from_str("444444")?, 3);
+        assert!(engine.ponder.is_none());
+        Ok(())
+    }
+
+    #[test]
+    fn evaluate_verbose() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        for moves in ["32164625", "5554224333234511764415115", "274552224131661"] {
+            let game = Game::from_str(moves)?;
+            let (score, bounds) = engine.evaluate_verbose(&game);
+            assert_eq!(score, Engine::new().evaluate(&game));
+
+            // Each step narrows the bounds, which always contain the score
+            assert!(bounds.windows(2).all(|steps| {
+                let ((min, max), (next_min, next_max)) = (steps[0], steps[1]);
+                next_min >= min && next_max <= max && next_max - next_min < max - min
+            }));
+            assert!(bounds
+                .iter()
+                .all(|&(min, max)| min <= score && score <= max));
+            assert_eq!(bounds.last(), Some(&(score, score)));
+        }
+
+        // The current player wins immediately
+        let game = Game::from_str("112233")?;
+        assert_eq!(engine.evaluate_verbose(&game), (18, vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn move_loss() -> Result<(), MoveError> {
+        let mut engine = Engine::new();
+
+        // The only move that does not lose blocks the opponent in the 6th column
+        let game = Game::from_str("5554224333234511764415115")?;
+        assert_eq!(engine.move_loss(&game, 5), Ok(0));
+        assert_eq!(engine.move_loss(&game, 0), Ok(12));
+        assert_eq!(engine.move_loss(&game, 4), Err(MoveError::ColumnFull));
+        assert_eq!(engine.move_loss(&game, 7), Err(MoveError::InvalidColumn));
+        Ok(())
     }
 
     #[test]
@@ -319,6 +2511,9 @@ mod tests {
             engine.evaluate_next(&game),
             [-2, -1, -1, 18, -2, -2, -3].map(Some)
         );
+        assert_eq!(engine.evaluate_move(&game, 3), Ok(18));
+        assert_eq!(engine.evaluate_move(&game, 6), Ok(-3));
+        assert_eq!(engine.plies_to_end(&game), 1);
 
         Ok(())
     }
@@ -1,7 +1,10 @@
-use std::fmt;
+use core::fmt;
 
 /// A list of possible errors caused by playing a move in a game.
+///
+/// More errors may be added for new kinds of moves, so matches on this enum need a wildcard arm.
 #[derive(Debug, PartialEq, Eq)]
+#[non_exhaustive]
 pub enum MoveError {
     /// The specified column was full.
     ColumnFull,
@@ -9,8 +12,25 @@ pub enum MoveError {
     InvalidColumn,
     /// The game is over and no more moves can be played.
     GameOver,
+    /// A piece in a position was not supported by another piece or the bottom of the board.
+    FloatingPiece,
+    /// The number of pieces of each player in a position was inconsistent with alternating turns.
+    UnbalancedPieces,
+    /// A position contained pieces that were played after the game was won.
+    PlayedAfterWin,
+    /// The input did not have the expected format.
+    InvalidFormat,
+    /// The piece would not land in the expected row of the specified column.
+    UnexpectedRow,
+    /// The requested number of moves was not within the game's recorded move history.
+    InvalidPly,
+    /// The bottom piece of the column to pop out did not belong to the current player, or the column was empty.
+    NotOwnPiece,
+    /// The move could lead to more moves than can be counted, which is only possible with pop outs.
+    TooManyMoves,
 }
 
+#[cfg(feature = "std")]
 impl std::error::Error for MoveError {}
 
 impl fmt::Display for MoveError {
@@ -20,6 +40,23 @@ impl fmt::Display for MoveError {
             ColumnFull => write!(f, "cannot play into a full column"),
             InvalidColumn => write!(f, "column is out of bounds or cannot be parsed"),
             GameOver => write!(f, "moves cannot be played after the game ends"),
+            FloatingPiece => write!(
+                f,
+                "position contains a piece that is not supported from below"
+            ),
+            UnbalancedPieces => write!(
+                f,
+                "position has piece counts inconsistent with alternating turns"
+            ),
+            PlayedAfterWin => write!(f, "position contains pieces played after the game was won"),
+            InvalidFormat => write!(f, "input does not have the expected format"),
+            UnexpectedRow => write!(f, "piece does not land in the expected row"),
+            InvalidPly => write!(f, "move count is outside the recorded move history"),
+            NotOwnPiece => write!(
+                f,
+                "bottom piece of the column does not belong to the current player"
+            ),
+            TooManyMoves => write!(f, "number of moves could exceed the maximum of 255"),
         }
     }
 }
@@ -1,10 +1,44 @@
 //! Functionality for creating and playing the game of Connect Four.
 
-use crate::{bitboard, Board, MoveError, Player, WinDirection, AREA, HEIGHT, WIDTH};
-use std::{array, collections::HashSet, fmt, str::FromStr};
+use crate::{
+    bitboard, Board, GameTranscript, MoveError, Player, AREA, CONNECT_LENGTH, HEIGHT, WIDTH,
+};
+use alloc::{
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{
+    array, fmt,
+    hash::{Hash, Hasher},
+    str::FromStr,
+};
+#[cfg(feature = "std")]
+use std::collections::HashSet;
+
+/// The number of bits used to store each move in the packed binary format.
+const PACKED_MOVE_BITS: usize = 3;
+
+/// The width and height in pixels of each tile of an SVG board.
+#[cfg(feature = "svg")]
+const SVG_TILE_SIZE: usize = 100;
+
+/// Named openings recognized by [`Game::opening_name`], given as strings of 1-indexed columns and the name of each opening.
+const OPENINGS: [(&str, &str); 9] = [
+    ("1", "Edge opening"),
+    ("2", "Flank opening"),
+    ("3", "Off-center opening"),
+    ("4", "Center opening"),
+    ("41", "Center opening, edge reply"),
+    ("42", "Center opening, flank reply"),
+    ("43", "Center opening, adjacent reply"),
+    ("44", "Center opening, follow-up reply"),
+    ("444", "Center tower"),
+];
 
 /// Represents the state of a game.
-#[derive(Debug, PartialEq, Eq)]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Status {
     /// The game has ended in a draw.
     Draw,
@@ -14,12 +48,145 @@ pub enum Status {
     Win(Player),
 }
 
+/// Represents the result of a finished game, or of a game position under perfect play by both players.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Outcome {
+    /// The game ends in a draw.
+    Draw,
+    /// The game ends with a winner represented by [`Player`].
+    Win(Player),
+}
+
+impl Status {
+    /// Returns the [`Outcome`] of a finished game, or [`None`] if the game is still ongoing.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Outcome, Player, Status};
+    ///
+    /// assert_eq!(Status::Win(Player::P2).outcome(), Some(Outcome::Win(Player::P2)));
+    /// assert_eq!(Status::Draw.outcome(), Some(Outcome::Draw));
+    /// assert_eq!(Status::Ongoing.outcome(), None);
+    /// ```
+    pub fn outcome(&self) -> Option<Outcome> {
+        match *self {
+            Status::Draw => Some(Outcome::Draw),
+            Status::Ongoing => None,
+            Status::Win(player) => Some(Outcome::Win(player)),
+        }
+    }
+}
+
+impl From<Status> for Option<Outcome> {
+    fn from(status: Status) -> Self {
+        status.outcome()
+    }
+}
+
+impl From<Outcome> for Status {
+    fn from(outcome: Outcome) -> Self {
+        match outcome {
+            Outcome::Draw => Status::Draw,
+            Outcome::Win(player) => Status::Win(player),
+        }
+    }
+}
+
+/// Represents the orientation of a line of pieces.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Direction {
+    /// A line going up and to the right (/).
+    AscendingDiagonal,
+    /// A line going down and to the right (\\).
+    DescendingDiagonal,
+    /// A line along a row (-).
+    Horizontal,
+    /// A line along a column (|).
+    Vertical,
+}
+
+/// Represents a winning line of [`connect_length`](Game::connect_length) pieces.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct WinLine {
+    /// The sorted `(x, y)` coordinates of the pieces in the line.
+    pub coords: Vec<(u8, u8)>,
+    /// The orientation of the line.
+    pub direction: Direction,
+    /// The player who owns the pieces in the line.
+    pub player: Player,
+}
+
+/// Represents a move played in a game.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MoveRecord {
+    /// The player who played the move.
+    pub player: Player,
+    /// The 0-indexed column of the move.
+    pub col: u8,
+    /// The 0-indexed row from the bottom that the piece landed in.
+    pub row: u8,
+}
+
+/// The number of each player's threats on odd and even rows, returned by [`Game::threat_parity`].
+///
+/// Rows are counted from 1 at the bottom. When the board fills up, the first player can usually only win with threats on odd rows,
+/// and the second player can usually only win with threats on even rows.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ThreatParity {
+    /// The number of the first player's threats on odd rows.
+    pub p1_odd: u8,
+    /// The number of the first player's threats on even rows.
+    pub p1_even: u8,
+    /// The number of the second player's threats on odd rows.
+    pub p2_odd: u8,
+    /// The number of the second player's threats on even rows.
+    pub p2_even: u8,
+}
+
+/// A well-known tactical pattern in a game position, returned by [`Game::detect_motifs`].
+///
+/// More motifs may be added in the future.
+#[non_exhaustive]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Motif {
+    /// The player has at least two playable tiles that would complete a winning line, so their opponent cannot block every one of them with a single move.
+    DoubleThreat(Player),
+}
+
+/// Counts of the leaf nodes in a game tree, returned by [`Game::perft_detailed`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct PerftStats {
+    /// The number of distinct sequences of moves with the given depth.
+    pub nodes: u64,
+    /// The number of leaf nodes where the last move won the game.
+    pub terminal_wins: u64,
+    /// The number of leaf nodes where the last move ended the game in a draw.
+    pub terminal_draws: u64,
+}
+
+/// Counts of the distinct positions at the leaves of a game tree, returned by [`Game::terminal_stats`].
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TerminalStats {
+    /// The number of positions where the first player has won.
+    pub p1_wins: u64,
+    /// The number of positions where the second player has won.
+    pub p2_wins: u64,
+    /// The number of positions where the board is full without a winner.
+    pub draws: u64,
+    /// The number of positions at the maximum depth where the game has not ended.
+    pub ongoing: u64,
+}
+
 /// Represents a Connect Four game.
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
 pub struct Game {
     pub(crate) board: Board,
     /// A vector of all the moves played in the game.
     moves: Vec<u8>,
+    /// The pop outs played in the game, each with its 0-indexed column and the move history that it cleared, so that they can be undone.
+    pop_outs: Vec<(u8, Vec<u8>)>,
+    /// The player who won the game, which is found when the winning move is played.
+    winner: Option<Player>,
 }
 
 impl Game {
@@ -34,6 +201,74 @@ impl Game {
         Self::default()
     }
 
+    /// Creates a new game with an empty board of the given dimensions, allowing variants such as 6x5 or 8x7 to be played.
+    ///
+    /// The [`Engine`](crate::Engine) can only solve games with the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Panics
+    /// Panics if either dimension is less than 4 or if the board does not fit inside a bitboard (`width * (height + 1) > 64`).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::with_dimensions(8, 7);
+    /// assert_eq!(game.width(), 8);
+    /// assert_eq!(game.height(), 7);
+    /// ```
+    pub fn with_dimensions(width: u8, height: u8) -> Self {
+        assert!(
+            width >= 4 && height >= 4,
+            "with_dimensions: dimensions are too small (minimum 4)"
+        );
+        assert!(
+            u16::from(width) * (u16::from(height) + 1) <= 64,
+            "with_dimensions: dimensions are too large to fit in a bitboard"
+        );
+
+        Self {
+            board: Board::with_dimensions(width, height),
+            ..Self::default()
+        }
+    }
+
+    /// Creates a new game with an empty board of the given dimensions, where a player needs `connect` pieces in a row to win,
+    /// allowing variants such as Connect Five to be played.
+    ///
+    /// The [`Engine`](crate::Engine) can only solve games with the standard dimensions of [`WIDTH`] x [`HEIGHT`] and [`CONNECT_LENGTH`].
+    ///
+    /// # Panics
+    /// Panics for the same dimensions as [`with_dimensions`](Game::with_dimensions),
+    /// or if `connect` is less than 4 or greater than both dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::with_connect_length(9, 6, 5);
+    /// game.play_slice(&[0, 0, 1, 1, 2, 2, 3, 3])?;
+    /// assert!(!game.is_over());
+    ///
+    /// game.play(4)?;
+    /// assert_eq!(game.win_coords(), Some([(0, 0), (1, 0), (2, 0), (3, 0)]));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn with_connect_length(width: u8, height: u8, connect: u8) -> Self {
+        assert!(
+            connect >= 4,
+            "with_connect_length: connect length is too short (minimum 4)"
+        );
+        assert!(
+            connect <= width.max(height),
+            "with_connect_length: connect length is too long to fit on the board"
+        );
+
+        Self {
+            board: Board::with_connect_length(width, height, connect),
+            ..Self::with_dimensions(width, height)
+        }
+    }
+
     /// Creates a new game from a string of 1-indexed columns.
     ///
     /// # Errors
@@ -53,6 +288,242 @@ impl Game {
         Ok(game)
     }
 
+    /// Creates a new game from a 2D array in the same row-major format as [`matrix`](Game::matrix).
+    ///
+    /// The move history of the position cannot be recovered, so [`moves`](Game::moves) is empty for the returned game.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the position cannot be reached in a game:
+    /// - [`MoveError::FloatingPiece`] if a piece is not stacked on another piece or the bottom of the board.
+    /// - [`MoveError::UnbalancedPieces`] if player 1 does not have the same number or one more piece than player 2.
+    /// - [`MoveError::PlayedAfterWin`] if the player to move already has four in a row.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError, Player};
+    ///
+    /// let game = Game::from_str("4453")?;
+    /// let copy = Game::from_matrix(game.matrix())?;
+    /// assert_eq!(copy.matrix(), game.matrix());
+    /// assert_eq!(copy.turn(), Player::P1);
+    ///
+    /// let mut grid = [[None; 7]; 6];
+    /// grid[1][3] = Some(Player::P1); // no piece below
+    /// assert_eq!(Game::from_matrix(grid), Err(MoveError::FloatingPiece));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn from_matrix(
+        grid: [[Option<Player>; WIDTH as usize]; HEIGHT as usize],
+    ) -> Result<Self, MoveError> {
+        let mut p1_bb = 0;
+        let mut p2_bb = 0;
+
+        for (y, row) in grid.iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let mask = 1 << (x * usize::from(HEIGHT + 1) + y);
+                match tile {
+                    Some(Player::P1) => p1_bb |= mask,
+                    Some(Player::P2) => p2_bb |= mask,
+                    None => (),
+                }
+            }
+        }
+
+        Self::from_pieces(p1_bb, p2_bb)
+    }
+
+    /// Creates a new game from a string of [`WIDTH`] * [`HEIGHT`] cells, where `'0'` is an empty tile, `'1'` is a piece of player 1, and `'2'` is a piece of player 2.
+    ///
+    /// The cells are listed in row-major order starting from the bottom-left corner, so the first [`WIDTH`] characters are the bottom row from left to right,
+    /// the next [`WIDTH`] characters are the row above it, and so on. This is distinct from [`from_str`](Game::from_str), which takes the order of moves.
+    ///
+    /// The move history of the position cannot be recovered, so [`moves`](Game::moves) is empty for the returned game.
+    ///
+    /// # Errors
+    /// Returns [`MoveError::InvalidFormat`] if the string has the wrong length or contains other characters,
+    /// or any other [`MoveError`] that [`from_matrix`](Game::from_matrix) returns for unreachable positions.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError, Player};
+    ///
+    /// let cells = "000120000010000000000000000000000000000000";
+    /// let game = Game::from_cells(cells)?;
+    ///
+    /// assert_eq!(game.at(3, 0), Some(Player::P1));
+    /// assert_eq!(game.at(4, 0), Some(Player::P2));
+    /// assert_eq!(game.at(3, 1), Some(Player::P1));
+    /// assert_eq!(game.turn(), Player::P2);
+    ///
+    /// assert_eq!(Game::from_cells("0001200"), Err(MoveError::InvalidFormat));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn from_cells(s: &str) -> Result<Self, MoveError> {
+        if s.len() != usize::from(AREA) {
+            return Err(MoveError::InvalidFormat);
+        }
+
+        let mut grid = [[None; WIDTH as usize]; HEIGHT as usize];
+        for (i, c) in s.chars().enumerate() {
+            grid[i / usize::from(WIDTH)][i % usize::from(WIDTH)] = match c {
+                '0' => None,
+                '1' => Some(Player::P1),
+                '2' => Some(Player::P2),
+                _ => return Err(MoveError::InvalidFormat),
+            };
+        }
+
+        Self::from_matrix(grid)
+    }
+
+    /// Creates a new game from the compact binary format returned by [`to_packed`](Game::to_packed).
+    ///
+    /// # Errors
+    /// Returns [`MoveError::InvalidFormat`] if the bytes are empty, do not match the length prefix, or have non-zero padding bits,
+    /// or any other [`MoveError`] at the first move that cannot be played.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError};
+    ///
+    /// let game = Game::from_packed(&[3, 0b0001_1011, 0b1])?;
+    /// assert_eq!(game.moves(), &[3, 3, 4]);
+    ///
+    /// assert_eq!(Game::from_packed(&[3, 0b0001_1011]), Err(MoveError::InvalidFormat));
+    /// assert_eq!(Game::from_packed(&[1, 0b111]), Err(MoveError::InvalidColumn));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn from_packed(bytes: &[u8]) -> Result<Self, MoveError> {
+        let (&len, packed) = bytes.split_first().ok_or(MoveError::InvalidFormat)?;
+        let num_bits = usize::from(len) * PACKED_MOVE_BITS;
+        if packed.len() != num_bits.div_ceil(8) {
+            return Err(MoveError::InvalidFormat);
+        }
+
+        let bit = |i: usize| (packed[i / 8] >> (i % 8)) & 1;
+        if (num_bits..packed.len() * 8).any(|i| bit(i) != 0) {
+            return Err(MoveError::InvalidFormat);
+        }
+
+        let mut game = Self::new();
+        for start in (0..num_bits).step_by(PACKED_MOVE_BITS) {
+            let col =
+                (0..PACKED_MOVE_BITS).fold(0, |col, offset| col | (bit(start + offset) << offset));
+            game.play(col)?;
+        }
+        Ok(game)
+    }
+
+    /// Returns the moves of the game in a compact binary format, which can be read by [`from_packed`](Game::from_packed).
+    ///
+    /// The first byte is the number of moves. Each following 0-indexed column is packed into 3 bits,
+    /// filling each byte from the least significant bit and continuing into the next byte, with any remaining bits of the last byte set to 0.
+    /// A full game of 42 moves is packed into 17 bytes.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`] and [`CONNECT_LENGTH`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("445")?;
+    /// assert_eq!(game.to_packed(), vec![3, 0b0001_1011, 0b1]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn to_packed(&self) -> Vec<u8> {
+        assert!(
+            self.width() == WIDTH
+                && self.height() == HEIGHT
+                && self.connect_length() == CONNECT_LENGTH,
+            "to_packed: game does not have standard dimensions"
+        );
+
+        let num_bits = self.moves.len() * PACKED_MOVE_BITS;
+        let mut bytes = vec![0; 1 + num_bits.div_ceil(8)];
+        bytes[0] = u8::try_from(self.moves.len()).unwrap();
+
+        for (i, &col) in self.moves.iter().enumerate() {
+            for offset in 0..PACKED_MOVE_BITS {
+                let bit = i * PACKED_MOVE_BITS + offset;
+                bytes[1 + bit / 8] |= ((col >> offset) & 1) << (bit % 8);
+            }
+        }
+        bytes
+    }
+
+    /// Creates a new game from bitboards of the current player's pieces and all pieces, as used by many bitboard-based engines.
+    ///
+    /// Each column is represented by [`HEIGHT`] + 1 bits from the bottom row upwards, starting from the first column at the least significant bit,
+    /// so the tile at column `x` and row `y` is bit `x * (HEIGHT + 1) + y`. The extra bit at the top of each column must be empty.
+    /// The move history of the position cannot be recovered, so [`moves`](Game::moves) is empty for the returned game.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the bitboards are invalid or the position cannot be reached in a game:
+    /// - [`MoveError::InvalidFormat`] if a bit is set outside the board, or if `player` has a bit that is not set in `occupied`.
+    /// - [`MoveError::FloatingPiece`] if a piece is not stacked on another piece or the bottom of the board.
+    /// - [`MoveError::UnbalancedPieces`] if `player` does not have the same number of pieces as the other player, or one fewer when the second player is to move.
+    /// - [`MoveError::PlayedAfterWin`] if the player to move already has four in a row.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError};
+    ///
+    /// let player = 1 << 21 | 1 << 28;
+    /// let occupied = player | 1 << 22 | 1 << 14;
+    /// let game = Game::from_bitboards(player, occupied)?;
+    /// assert_eq!(game.matrix(), Game::from_str("4453")?.matrix());
+    ///
+    /// assert_eq!(Game::from_bitboards(0, 1 << 6), Err(MoveError::InvalidFormat));
+    /// assert_eq!(Game::from_bitboards(0, 1 << 1), Err(MoveError::FloatingPiece));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn from_bitboards(player: u64, occupied: u64) -> Result<Self, MoveError> {
+        if occupied & !bitboard::FULL_BOARD_MASK != 0 || player & !occupied != 0 {
+            return Err(MoveError::InvalidFormat);
+        }
+
+        let opponent = occupied ^ player;
+        if occupied.count_ones().is_multiple_of(2) {
+            Self::from_pieces(player, opponent)
+        } else {
+            Self::from_pieces(opponent, player)
+        }
+    }
+
+    /// Creates a new standard game from non-overlapping bitboards of each player's pieces, validating that the position is reachable.
+    fn from_pieces(p1_bb: u64, p2_bb: u64) -> Result<Self, MoveError> {
+        let occupied_bb = p1_bb | p2_bb;
+        if (occupied_bb + bitboard::BOTTOM_ROW_MASK) & occupied_bb != 0 {
+            return Err(MoveError::FloatingPiece);
+        }
+
+        let p1_count = p1_bb.count_ones();
+        let p2_count = p2_bb.count_ones();
+        let player_bb = if p1_count == p2_count {
+            p1_bb
+        } else if p1_count == p2_count + 1 {
+            p2_bb
+        } else {
+            return Err(MoveError::UnbalancedPieces);
+        };
+
+        let board = Board::from_bitboards(player_bb, occupied_bb);
+        if board.check_win(player_bb).is_some() {
+            return Err(MoveError::PlayedAfterWin);
+        }
+
+        Ok(Self {
+            board,
+            winner: board.has_opponent_won().then_some(if p1_count == p2_count {
+                Player::P2
+            } else {
+                Player::P1
+            }),
+            ..Self::default()
+        })
+    }
+
     /// Plays the current player's piece in the given 0-indexed column.
     ///
     /// # Errors
@@ -72,7 +543,11 @@ impl Game {
     /// ```
     pub fn play(&mut self, col: u8) -> Result<(), MoveError> {
         self.can_play(col)?;
-        self.board.play_unchecked(col);
+        let player = self.turn();
+        let move_bb = self.board.possible_bb() & bitboard::column_mask(col, self.height());
+        if self.board.play_and_check(move_bb) {
+            self.winner = Some(player);
+        }
         self.moves.push(col);
         Ok(())
     }
@@ -100,6 +575,117 @@ impl Game {
         Ok(())
     }
 
+    /// Plays the current player's piece in the given 0-indexed column, checking that it lands in the given 0-indexed row from the bottom.
+    ///
+    /// # Errors
+    /// Returns [`MoveError::UnexpectedRow`] if the piece would land in a different row, or any other [`MoveError`] if the move cannot be played.
+    /// The game is unchanged if an error is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.play_at(3, 0), Ok(()));
+    /// assert_eq!(game.play_at(3, 0), Err(MoveError::UnexpectedRow));
+    /// assert_eq!(game.play_at(3, 1), Ok(()));
+    /// assert_eq!(game.play_at(7, 0), Err(MoveError::InvalidColumn));
+    /// ```
+    pub fn play_at(&mut self, col: u8, expected_row: u8) -> Result<(), MoveError> {
+        if self.can_play(col)? != expected_row {
+            return Err(MoveError::UnexpectedRow);
+        }
+        self.play(col)
+    }
+
+    /// Removes the current player's piece from the bottom of the given 0-indexed column, as in the Pop Out variant of Connect Four.
+    ///
+    /// The rest of the column shifts down one row and the turn passes to the opponent, so a pop out counts as a move in [`num_moves`](Game::num_moves).
+    /// Shifting the column can complete lines for either player. If both players have a line, the player who popped out wins.
+    /// A pop out cannot be recorded as a column, so the move history is cleared, like a game created with [`from_matrix`](Game::from_matrix),
+    /// but [`undo`](Game::undo) can still undo the pop out and restore the history.
+    ///
+    /// The [`Engine`](crate::Engine) cannot solve games with pop outs, and panics if given one.
+    ///
+    /// # Errors
+    /// Returns [`MoveError::NotOwnPiece`] if the column is empty or its bottom piece belongs to the opponent, [`MoveError::GameOver`] if the game is over,
+    /// [`MoveError::TooManyMoves`] if filling the board afterwards could take more moves than can be counted, or [`MoveError::InvalidColumn`] if the column is out of bounds.
+    /// The game is unchanged if an error is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError, Player};
+    ///
+    /// let mut game = Game::from_str("4534")?;
+    /// assert_eq!(game.pop_out(4), Err(MoveError::NotOwnPiece));
+    ///
+    /// game.pop_out(3)?;
+    /// assert_eq!(game.at(3, 0), Some(Player::P2));
+    /// assert_eq!(game.at(3, 1), None);
+    /// assert_eq!(game.turn(), Player::P2);
+    /// assert_eq!(game.num_moves(), 5);
+    /// assert!(game.moves().is_empty());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn pop_out(&mut self, col: u8) -> Result<(), MoveError> {
+        if self.is_over() {
+            return Err(MoveError::GameOver);
+        } else if col >= self.width() {
+            return Err(MoveError::InvalidColumn);
+        } else if self.at(col, 0) != Some(self.turn()) {
+            return Err(MoveError::NotOwnPiece);
+        }
+
+        // Every empty tile can still be filled afterwards without the number of moves overflowing
+        let empty_tiles = self.board.area() - self.board.num_pieces() + 1;
+        if u16::from(self.num_moves()) + 1 + u16::from(empty_tiles) > u16::from(u8::MAX) {
+            return Err(MoveError::TooManyMoves);
+        }
+
+        let player = self.turn();
+        self.board.pop_unchecked(col);
+        self.pop_outs.push((col, core::mem::take(&mut self.moves)));
+
+        self.winner = if self.board.has_opponent_won() {
+            Some(player)
+        } else if self.board.check_win(self.board.player_bb()).is_some() {
+            Some(!player)
+        } else {
+            None
+        };
+        Ok(())
+    }
+
+    /// Plays the current player's piece in a uniformly random column that is not full, returning the 0-indexed column,
+    /// or [`None`] if the game is over.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    /// use rand::{rngs::SmallRng, SeedableRng};
+    ///
+    /// let mut rng = SmallRng::seed_from_u64(0);
+    /// let mut game = Game::new();
+    ///
+    /// while game.play_random(&mut rng).is_some() {}
+    /// assert!(game.is_over());
+    /// ```
+    #[cfg(feature = "rand")]
+    pub fn play_random<R: rand::Rng + ?Sized>(&mut self, rng: &mut R) -> Option<u8> {
+        let mut mask = self.legal_moves_mask();
+        if mask == 0 {
+            return None;
+        }
+
+        // Clear the lowest set bits until the randomly chosen column is the lowest
+        for _ in 0..rng.gen_range(0..mask.count_ones()) {
+            mask &= mask - 1;
+        }
+        let col = mask.trailing_zeros() as u8;
+        self.play(col).expect("move should be playable");
+        Some(col)
+    }
+
     /// Plays a sequence of moves from a string of 1-indexed columns.
     ///
     /// # Errors
@@ -123,21 +709,70 @@ impl Game {
     /// assert_eq!(result, Err(MoveError::InvalidColumn));
     /// ```
     pub fn play_str(&mut self, moves: &str) -> Result<(), MoveError> {
+        self.play_str_indexed(moves).map_err(|(_, error)| error)
+    }
+
+    /// Plays a sequence of moves from a string of 1-indexed columns like [`play_str`](Game::play_str),
+    /// but ignores ASCII whitespace and the separators `,`, `-`, and `.`, such as in pasted move lists.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] at the first move that cannot be played, including for any other characters.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError};
+    ///
+    /// let mut game = Game::new();
+    /// game.play_str_lenient("4, 4, 5 - 3.")?;
+    /// assert_eq!(game.moves_str(), "4453");
+    ///
+    /// assert_eq!(game.play_str_lenient("1;2"), Err(MoveError::InvalidColumn));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn play_str_lenient(&mut self, moves: &str) -> Result<(), MoveError> {
+        let moves: String = moves
+            .chars()
+            .filter(|&c| !c.is_ascii_whitespace() && !matches!(c, ',' | '-' | '.'))
+            .collect();
+        self.play_str(&moves)
+    }
+
+    /// Checks whether a string of 1-indexed columns is a valid sequence of moves from the starting position, without returning the game.
+    ///
+    /// # Errors
+    /// Returns the 0-indexed position of the first move that cannot be played in the string, along with its [`MoveError`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveError};
+    ///
+    /// assert_eq!(Game::validate_str("4455"), Ok(()));
+    /// assert_eq!(Game::validate_str("4444444"), Err((6, MoveError::ColumnFull)));
+    /// assert_eq!(Game::validate_str("12a"), Err((2, MoveError::InvalidColumn)));
+    /// ```
+    pub fn validate_str(moves: &str) -> Result<(), (usize, MoveError)> {
+        Self::new().play_str_indexed(moves)
+    }
+
+    /// Plays a sequence of moves from a string of 1-indexed columns, returning the position of the first move that cannot be played along with its error.
+    fn play_str_indexed(&mut self, moves: &str) -> Result<(), (usize, MoveError)> {
         fn char_to_col(c: char) -> Option<u8> {
             let n = c.to_digit(10)?;
             let n = u8::try_from(n).unwrap();
             n.checked_sub(1)
         }
 
-        for c in moves.chars() {
-            let col = char_to_col(c).ok_or(MoveError::InvalidColumn)?;
-            self.play(col)?;
+        for (i, c) in moves.chars().enumerate() {
+            let col = char_to_col(c).ok_or((i, MoveError::InvalidColumn))?;
+            self.play(col).map_err(|error| (i, error))?;
         }
         Ok(())
     }
 
     /// Undoes the last move played and returns the 0-indexed column. Returns [`None`] if no moves have been made.
     ///
+    /// A [pop out](Game::pop_out) is also undone, returning its column and restoring the move history before it.
+    ///
     /// # Examples
     /// ```
     /// use connect_four_engine::Game;
@@ -151,630 +786,3109 @@ impl Game {
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
     pub fn undo(&mut self) -> Option<u8> {
-        let col = self.moves.pop()?;
-        self.board.undo_unchecked(col);
+        // No moves can be played after a win, so the game was not won before the undone move
+        if let Some(col) = self.moves.pop() {
+            self.board.undo_unchecked(col);
+            self.winner = None;
+            return Some(col);
+        }
+
+        let (col, moves) = self.pop_outs.pop()?;
+        self.board.unpop_unchecked(col);
+        self.moves = moves;
+        self.winner = None;
         Some(col)
     }
 
-    /// Checks if a piece can be played in a given 0-indexed column, returning the number of pieces in the column.
-    ///
-    /// # Errors
-    /// Returns a [`MoveError`] if the move cannot be played.
+    /// Undoes up to `n` moves, returning the number of moves that were actually undone.
+    /// Fewer than `n` moves are undone if the move history runs out.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Game, MoveError};
+    /// use connect_four_engine::Game;
     ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.can_play(3), Ok(0));
+    /// let mut game = Game::from_str("4453")?;
     ///
-    /// game.play(3)?;
-    /// assert_eq!(game.can_play(3), Ok(1));
+    /// assert_eq!(game.undo_n(2), 2);
+    /// assert_eq!(game.moves(), &[3, 3]);
     ///
-    /// game.play_slice(&[3, 3, 3, 3, 3])?;
-    /// assert_eq!(game.can_play(3), Err(MoveError::ColumnFull)); // column is full
-    /// # Ok::<(), MoveError>(())
+    /// assert_eq!(game.undo_n(5), 2);
+    /// assert_eq!(game, Game::new());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn can_play(&self, col: u8) -> Result<u8, MoveError> {
-        if self.is_over() {
-            Err(MoveError::GameOver)
-        } else if col >= WIDTH {
-            Err(MoveError::InvalidColumn)
-        } else if !self.board.is_open(col) {
-            Err(MoveError::ColumnFull)
-        } else {
-            Ok(self.board.pieces_in_col(col))
-        }
+    pub fn undo_n(&mut self, n: usize) -> usize {
+        (0..n).take_while(|_| self.undo().is_some()).count()
     }
 
-    /// Returns the [`Player`] whose turn it currently is.
+    /// Rolls the game back to the position after the first `ply` moves.
+    ///
+    /// When fewer than half of the moves are kept, the board is rebuilt from an empty board instead of undoing each move.
+    ///
+    /// # Errors
+    /// Returns [`MoveError::InvalidPly`] if `ply` is greater than [`num_moves`](Game::num_moves),
+    /// or if reaching it would undo moves that are not in the move history, such as for games created with [`from_matrix`](Game::from_matrix).
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Game, Player};
+    /// use connect_four_engine::{Game, MoveError};
     ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.turn(), Player::P1);
+    /// let mut game = Game::from_str("44536212")?;
     ///
-    /// game.play(3)?;
-    /// assert_eq!(game.turn(), Player::P2);
+    /// game.truncate_to(3)?;
+    /// assert_eq!(game.moves_str(), "445");
     ///
-    /// game.play(1)?;
-    /// assert_eq!(game.turn(), Player::P1);
+    /// assert_eq!(game.truncate_to(4), Err(MoveError::InvalidPly));
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn turn(&self) -> Player {
-        if self.num_moves() % 2 == 0 {
-            Player::P1
+    pub fn truncate_to(&mut self, ply: u8) -> Result<(), MoveError> {
+        let num_moves = self.num_moves();
+        let history_start = usize::from(num_moves) - self.moves.len();
+        if ply > num_moves || usize::from(ply) < history_start {
+            return Err(MoveError::InvalidPly);
+        }
+
+        if history_start == 0 && ply < num_moves / 2 {
+            self.moves.truncate(ply.into());
+            self.board =
+                Board::with_connect_length(self.width(), self.height(), self.connect_length());
+            for &col in &self.moves {
+                self.board.play_unchecked(col);
+            }
+            self.winner = None;
         } else {
-            Player::P2
+            self.undo_n((num_moves - ply).into());
         }
+        Ok(())
     }
 
-    /// Returns the number of moves made in the game.
+    /// Returns an iterator that replays the recorded moves from the position where the move history begins,
+    /// yielding a new game after each move and ending at the current state.
+    ///
+    /// The history begins at an empty board, or at the loaded position for games created with [`from_matrix`](Game::from_matrix),
+    /// or after the last [pop out](Game::pop_out). A new game is allocated at every step, and the original game is not modified.
     ///
     /// # Examples
     /// ```
     /// use connect_four_engine::Game;
     ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.num_moves(), 0);
-    ///
-    /// game.play(3)?;
-    /// assert_eq!(game.num_moves(), 1);
+    /// let game = Game::from_str("4453")?;
+    /// let states: Vec<Game> = game.replay().collect();
     ///
-    /// game.play_slice(&[0, 4, 6, 3])?;
-    /// assert_eq!(game.num_moves(), 5);
+    /// assert_eq!(states.len(), 4);
+    /// assert_eq!(states[0].moves_str(), "4");
+    /// assert_eq!(states[1].moves_str(), "44");
+    /// assert_eq!(states[3], game);
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn num_moves(&self) -> u8 {
-        self.board.num_moves()
+    pub fn replay(&self) -> impl Iterator<Item = Game> + '_ {
+        let mut game = self.clone();
+        game.undo_n(self.moves.len());
+
+        self.moves.iter().map(move |&col| {
+            game.play(col).expect("recorded move should be playable");
+            game.clone()
+        })
     }
 
-    /// Returns a slice of all the moves played in the game as 0-indexed columns.
+    /// Returns every position reachable with one move, pairing each playable 0-indexed column with a copy of the game after playing it.
+    ///
+    /// Full columns are skipped, and the vector is empty if the game is over.
     ///
     /// # Examples
     /// ```
     /// use connect_four_engine::Game;
     ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.moves(), &[]);
-    ///
-    /// game.play(3)?;
-    /// assert_eq!(game.moves(), &[3]);
+    /// let game = Game::from_str("444444")?;
+    /// let children = game.children();
     ///
-    /// game.play_slice(&[0, 4, 6, 3])?;
-    /// assert_eq!(game.moves(), &[3, 0, 4, 6, 3]);
+    /// assert_eq!(children.len(), 6);
+    /// assert_eq!(children[3].0, 4);
+    /// assert_eq!(children[3].1.moves_str(), "4444445");
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn moves(&self) -> &[u8] {
-        &self.moves
+    pub fn children(&self) -> Vec<(u8, Game)> {
+        (0..self.width())
+            .filter_map(|col| {
+                let mut child = self.clone();
+                child.play(col).ok()?;
+                Some((col, child))
+            })
+            .collect()
     }
 
-    /// Returns a string of all the moves played in the game as 1-indexed columns.
+    /// Checks if a piece can be played in a given 0-indexed column, returning the number of pieces in the column.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the move cannot be played.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::Game;
+    /// use connect_four_engine::{Game, MoveError};
     ///
     /// let mut game = Game::new();
-    /// assert_eq!(game.moves_str(), "");
+    /// assert_eq!(game.can_play(3), Ok(0));
     ///
-    /// game.play_str("1234567")?;
-    /// assert_eq!(game.moves_str(), "1234567");
-    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// game.play(3)?;
+    /// assert_eq!(game.can_play(3), Ok(1));
+    ///
+    /// game.play_slice(&[3, 3, 3, 3, 3])?;
+    /// assert_eq!(game.can_play(3), Err(MoveError::ColumnFull)); // column is full
+    /// # Ok::<(), MoveError>(())
     /// ```
-    pub fn moves_str(&self) -> String {
-        let mut s = String::with_capacity(self.num_moves().into());
-        for col in self.moves() {
-            s.push(char::from_digit((col + 1).into(), 10).unwrap());
+    pub fn can_play(&self, col: u8) -> Result<u8, MoveError> {
+        if self.is_over() {
+            Err(MoveError::GameOver)
+        } else if col >= self.width() {
+            Err(MoveError::InvalidColumn)
+        } else if !self.board.is_open(col) {
+            Err(MoveError::ColumnFull)
+        } else {
+            Ok(self.board.pieces_in_col(col))
         }
-        s
     }
 
-    /// Returns a [`Status`] representing the current state of the game.
+    /// Returns a bitset of the 0-indexed columns that can be played, where bit `i` is set if column `i` is not full.
+    /// No bits are set if the game is over.
+    ///
+    /// This is a `u16` rather than a `u8` because boards shorter than the standard height can have up to 12 columns.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Game, Player, Status};
-    ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.status(), Status::Ongoing);
+    /// use connect_four_engine::Game;
     ///
-    /// game.play(3)?;
-    /// assert_eq!(game.status(), Status::Ongoing);
+    /// let game = Game::from_str("444444111111")?;
+    /// assert_eq!(game.legal_moves_mask(), 0b1110110);
     ///
-    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
-    /// assert_eq!(game.status(), Status::Win(Player::P1));
+    /// let game = Game::from_str("4455667")?;
+    /// assert_eq!(game.legal_moves_mask(), 0);
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn status(&self) -> Status {
-        if self.board.has_opponent_won() {
-            Status::Win(!self.turn())
-        } else if self.board.is_full() {
-            Status::Draw
-        } else {
-            Status::Ongoing
+    pub fn legal_moves_mask(&self) -> u16 {
+        if self.is_over() {
+            return 0;
         }
+        (0..self.width())
+            .filter(|&col| self.board.is_open(col))
+            .fold(0, |mask, col| mask | 1 << col)
     }
 
-    /// Checks if the game is over and no more moves can be played.
+    /// Returns the 0-indexed columns that can be played from left to right, or an empty vector if the game is over.
     ///
     /// # Examples
     /// ```
     /// use connect_four_engine::Game;
     ///
-    /// let mut game = Game::new();
-    /// assert!(!game.is_over());
+    /// let game = Game::from_str("444444111111")?;
+    /// assert_eq!(game.legal_moves(), vec![1, 2, 4, 5, 6]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn legal_moves(&self) -> Vec<u8> {
+        let mask = self.legal_moves_mask();
+        (0..self.width())
+            .filter(|&col| mask & 1 << col != 0)
+            .collect()
+    }
+
+    /// Returns the number of pieces in each column, from left to right.
     ///
-    /// game.play(3)?;
-    /// assert!(!game.is_over());
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
     ///
-    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
-    /// assert!(game.is_over());
+    /// let game = Game::from_str("44453")?;
+    /// assert_eq!(game.column_heights(), vec![0, 0, 1, 3, 1, 0, 0]);
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn is_over(&self) -> bool {
-        self.board.is_terminal()
+    pub fn column_heights(&self) -> Vec<u8> {
+        (0..self.width())
+            .map(|col| self.board.pieces_in_col(col))
+            .collect()
     }
 
-    /// Returns an array containing the `(x, y)` coordinates of four pieces that form a winning line horizontally, vertically, or diagonally. If no line exists (there is no winner), then [`None`] is returned.
+    /// Checks if a given 0-indexed column is full. Columns outside of the board are always full.
     ///
-    /// The order of coordinates is sorted. If there are multiple winning lines, then any one of the lines can be returned.
+    /// Unlike [`can_play`](Game::can_play), this does not depend on whether the game is over.
     ///
     /// # Examples
     /// ```
     /// use connect_four_engine::Game;
     ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.win_coords(), None);
-    ///
-    /// game.play(3)?;
-    /// assert_eq!(game.win_coords(), None);
-    ///
-    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
-    /// assert_eq!(game.win_coords(), Some([(3, 0), (3, 1), (3, 2), (3, 3)]));
+    /// let game = Game::from_str("444444")?;
+    /// assert!(game.is_column_full(3));
+    /// assert!(!game.is_column_full(4));
+    /// assert!(game.is_column_full(7));
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn win_coords(&self) -> Option<[(u8, u8); 4]> {
-        let (bitboard, direction) = self.board.opponent_winning_bb()?;
-
-        let index = u8::try_from(bitboard.trailing_zeros()).unwrap();
-        let start_col = index / (HEIGHT + 1);
-        let start_row = index % (HEIGHT + 1);
-
-        use WinDirection::*;
-        Some(match direction {
-            AscendingDiagonal => array::from_fn(|i| {
-                let i = u8::try_from(i).unwrap();
-                (start_col + i, start_row + i)
-            }),
-            DescendingDiagonal => array::from_fn(|i| {
-                let i = u8::try_from(i).unwrap();
-                (start_col + i, start_row - i)
-            }),
-            Horizontal => array::from_fn(|i| {
-                let i = u8::try_from(i).unwrap();
-                (start_col + i, start_row)
-            }),
-            Vertical => array::from_fn(|i| {
-                let i = u8::try_from(i).unwrap();
-                (start_col, start_row + i)
-            }),
-        })
+    pub fn is_column_full(&self, col: u8) -> bool {
+        col >= self.width() || !self.board.is_open(col)
     }
 
-    /// Returns the [`Player`] who owns the piece at `(x, y)`, or [`None`] if the tile is empty.
+    /// Checks if the current player wins immediately by playing in a given 0-indexed column.
     ///
-    /// # Panics
-    /// Panics if given a coordinate that is out of bounds (x >= [`WIDTH`] or y >= [`HEIGHT`]).
+    /// # Errors
+    /// Returns a [`MoveError`] if the move cannot be played.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Game, Player};
+    /// use connect_four_engine::{Game, MoveError};
     ///
-    /// let mut game = Game::new();
-    /// game.play(0)?;
-    /// game.play(1)?;
+    /// let game = Game::from_str("112233")?;
+    /// assert_eq!(game.is_winning_move(3), Ok(true));
+    /// assert_eq!(game.is_winning_move(4), Ok(false));
+    /// assert_eq!(game.is_winning_move(7), Err(MoveError::InvalidColumn));
+    /// # Ok::<(), MoveError>(())
+    /// ```
+    pub fn is_winning_move(&self, col: u8) -> Result<bool, MoveError> {
+        self.can_play(col)?;
+        Ok(self.board.is_winning_move(col))
+    }
+
+    /// Checks if playing the current player's piece in the given 0-indexed column creates a fork,
+    /// where the current player then has at least two playable winning moves so the opponent cannot block all of them.
     ///
-    /// assert_eq!(game.at(0, 0), Some(Player::P1));
-    /// assert_eq!(game.at(1, 0), Some(Player::P2));
-    /// assert_eq!(game.at(2, 0), None);
-    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// A move that wins immediately does not create a fork. Whether the opponent can win first is not considered.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the move cannot be played.
+    ///
+    /// # Examples
     /// ```
+    /// use connect_four_engine::{Game, MoveError};
     ///
-    /// Passing a coordinate that is out of bounds, causing a panic:
-    /// ```should_panic
-    /// # use connect_four_engine::Game;
-    /// # let game = Game::new();
-    /// let _ = game.at(7, 0); // this panics
+    /// // _ _ _ _ _ _ _
+    /// // _ _ O O _ _ _
+    /// // _ _ X X _ _ _
+    /// let game = Game::from_str("3344")?;
+    ///
+    /// assert_eq!(game.creates_fork(4), Ok(true));
+    /// assert_eq!(game.creates_fork(6), Ok(false));
+    /// assert_eq!(game.creates_fork(7), Err(MoveError::InvalidColumn));
+    /// # Ok::<(), MoveError>(())
     /// ```
-    pub fn at(&self, x: u8, y: u8) -> Option<Player> {
-        assert!(x < WIDTH, "at: x is out of bounds (maximum {WIDTH})");
-        assert!(y < HEIGHT, "at: y is out of bounds (maximum {HEIGHT})");
-
-        let turn = self.turn();
-        let offset = x * WIDTH + y;
-        let mask = 1 << offset;
-
-        if self.board.player_bb() & mask != 0 {
-            Some(turn)
-        } else if self.board.opponent_bb() & mask != 0 {
-            Some(!turn)
-        } else {
-            None
+    pub fn creates_fork(&self, col: u8) -> Result<bool, MoveError> {
+        self.can_play(col)?;
+        if self.board.is_winning_move(col) {
+            return Ok(false);
         }
+
+        let mut board = self.board;
+        board.play_unchecked(col);
+        Ok(board.playable_winning_bb(board.opponent_bb()).count_ones() >= 2)
     }
 
-    /// Returns the current state of the game as a 2D array where each element is [`Some(Player)`] if that player owns a piece at the location or [`None`] if the tile is empty.
+    /// Returns the 0-indexed columns where the current player can win with their next move, followed by the columns where the opponent could win if it were their turn.
+    ///
+    /// Only playable columns are listed, and both lists are empty if the game is over.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::{Game, Player};
-    ///
-    /// let mut game = Game::new();
-    /// assert_eq!(game.matrix()[0][3], None);
+    /// use connect_four_engine::Game;
     ///
-    /// game.play(3)?;
-    /// assert_eq!(game.matrix()[0][3], Some(Player::P1));
+    /// // _ _ _ _ _ _ _
+    /// // _ _ O O _ _ _
+    /// // _ _ X X X _ _
+    /// let game = Game::from_str("33445")?;
+    /// assert_eq!(game.threats(), (vec![], vec![1, 5]));
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    /// The 2D array is in row-major order:
+    pub fn threats(&self) -> (Vec<u8>, Vec<u8>) {
+        if self.is_over() {
+            return (Vec::new(), Vec::new());
+        }
+
+        let columns = |bitboard: u64| -> Vec<u8> {
+            let winning_bb = self.board.playable_winning_bb(bitboard);
+            (0..self.width())
+                .filter(|&col| winning_bb & bitboard::column_mask(col, self.height()) != 0)
+                .collect()
+        };
+
+        (
+            columns(self.board.player_bb()),
+            columns(self.board.opponent_bb()),
+        )
+    }
+
+    /// Checks if the position is quiet, where neither player could win with their next move in a playable column.
+    ///
+    /// This is equivalent to both lists of [`threats`](Game::threats) being empty, without allocating them. A game that is over is always quiet.
+    ///
+    /// # Examples
     /// ```
-    /// # use connect_four_engine::Game;
-    /// # let game = Game::from_str("111112222233333144444255555376666667777754")?;
-    /// # let x = 0;
-    /// # let y = 5;
-    /// let a = game.matrix()[y][x];
-    /// # let x = 0;
-    /// # let y = 5;
-    /// let b = game.at(x, y);
-    /// assert_eq!(a, b);
+    /// use connect_four_engine::Game;
+    ///
+    /// assert!(Game::from_str("4453")?.is_quiet());
+    /// assert!(!Game::from_str("33445")?.is_quiet());
     /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn matrix(&self) -> [[Option<Player>; WIDTH as usize]; HEIGHT as usize] {
-        array::from_fn(|y| {
-            array::from_fn(|x| self.at(x.try_into().unwrap(), y.try_into().unwrap()))
-        })
+    pub fn is_quiet(&self) -> bool {
+        self.is_over()
+            || self.board.playable_winning_bb(self.board.player_bb())
+                | self.board.playable_winning_bb(self.board.opponent_bb())
+                == 0
     }
 
-    /// Returns the number of unique game positions at a specific depth.
-    ///
-    /// # Warning
-    /// Running this at a large depth (>14) is computationally expensive.
+    /// Counts the threats of each player by the parity of their rows, where a threat is an empty tile that completes a winning line for the player.
     ///
-    /// # Panics
-    /// Panics if given a depth larger than [`WIDTH`] * [`HEIGHT`].
+    /// Unlike [`threats`](Game::threats), threats that cannot be played yet because the tiles below them are empty are also counted.
+    /// Every count is 0 if the game is over.
     ///
     /// # Examples
     /// ```
-    /// use connect_four_engine::Game;
+    /// use connect_four_engine::{Game, ThreatParity};
     ///
-    /// assert_eq!(Game::perft(0), 1);
-    /// assert_eq!(Game::perft(1), 7);
-    /// assert_eq!(Game::perft(2), 49);
-    /// assert_eq!(Game::perft(3), 238);
-    /// ```
+    /// // _ _ _ _ _ _ _
+    /// // _ _ O O O _ _
+    /// // _ _ X X X _ _
+    /// let game = Game::from_str("334455")?;
+    /// let parity = game.threat_parity();
     ///
-    /// Passing a large depth, causing a panic:
-    /// ```should_panic
-    /// use connect_four_engine::Game;
-    /// Game::perft(43); // this panics
+    /// assert_eq!(parity, ThreatParity { p1_odd: 2, p1_even: 0, p2_odd: 0, p2_even: 2 });
+    /// # Ok::<(), connect_four_engine::MoveError>(())
     /// ```
-    pub fn perft(depth: u8) -> u64 {
-        assert!(depth <= AREA, "perft: depth is too high (maximum {})", AREA);
-        let board = Board::new();
-        Self::count_nodes(board, depth, &mut HashSet::new())
-    }
+    pub fn threat_parity(&self) -> ThreatParity {
+        if self.is_over() {
+            return ThreatParity::default();
+        }
 
-    /// Helper function for perft.
-    fn count_nodes(board: Board, depth: u8, seen: &mut HashSet<u64>) -> u64 {
-        seen.insert(board.key());
+        let (p1_bb, p2_bb) = if self.turn() == Player::P1 {
+            (self.board.player_bb(), self.board.opponent_bb())
+        } else {
+            (self.board.opponent_bb(), self.board.player_bb())
+        };
 
-        if depth == 0 {
-            return 1;
-        }
+        // Odd rows counting from 1 are even rows counting from 0
+        let odd_rows_mask = (0..self.height()).step_by(2).fold(0, |mask, row| {
+            mask | bitboard::bottom_row_mask(self.width(), self.height()) << row
+        });
 
-        if board.is_terminal() {
-            return 0;
-        }
+        let count = |bitboard: u64| -> u8 { bitboard.count_ones().try_into().unwrap() };
+        let p1_threats = self.board.winning_bb(p1_bb);
+        let p2_threats = self.board.winning_bb(p2_bb);
 
-        let mut nodes = 0;
+        ThreatParity {
+            p1_odd: count(p1_threats & odd_rows_mask),
+            p1_even: count(p1_threats & !odd_rows_mask),
+            p2_odd: count(p2_threats & odd_rows_mask),
+            p2_even: count(p2_threats & !odd_rows_mask),
+        }
+    }
 
-        for i in 0..WIDTH {
-            if board.is_open(i) {
-                let mut new_board = board;
-                new_board.play_unchecked(i);
-                if !seen.contains(&new_board.key()) {
-                    nodes += Self::count_nodes(new_board, depth - 1, seen);
+    /// Returns the tactical [`Motif`]s found in the position, listing the current player's motifs before the opponent's.
+    ///
+    /// The vector is empty if the game is over.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Motif, Player};
+    ///
+    /// // _ _ _ _ _ _ _
+    /// // _ _ O O _ _ _
+    /// // _ _ X X X _ _
+    /// let game = Game::from_str("33445")?;
+    /// assert_eq!(game.detect_motifs(), vec![Motif::DoubleThreat(Player::P1)]);
+    /// assert!(Game::from_str("4453")?.detect_motifs().is_empty());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn detect_motifs(&self) -> Vec<Motif> {
+        if self.is_over() {
+            return Vec::new();
+        }
+
+        [
+            (self.turn(), self.board.player_bb()),
+            (!self.turn(), self.board.opponent_bb()),
+        ]
+        .into_iter()
+        .filter(|&(_, bitboard)| self.board.playable_winning_bb(bitboard).count_ones() >= 2)
+        .map(|(player, _)| Motif::DoubleThreat(player))
+        .collect()
+    }
+
+    /// Returns the number of columns in the board.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, WIDTH};
+    ///
+    /// assert_eq!(Game::new().width(), WIDTH);
+    /// assert_eq!(Game::with_dimensions(6, 5).width(), 6);
+    /// ```
+    pub fn width(&self) -> u8 {
+        self.board.width()
+    }
+
+    /// Returns the number of pieces in a row needed to win.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, CONNECT_LENGTH};
+    ///
+    /// assert_eq!(Game::new().connect_length(), CONNECT_LENGTH);
+    /// assert_eq!(Game::with_connect_length(8, 7, 5).connect_length(), 5);
+    /// ```
+    pub fn connect_length(&self) -> u8 {
+        self.board.connect()
+    }
+
+    /// Returns the number of rows in the board.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, HEIGHT};
+    ///
+    /// assert_eq!(Game::new().height(), HEIGHT);
+    /// assert_eq!(Game::with_dimensions(6, 5).height(), 5);
+    /// ```
+    pub fn height(&self) -> u8 {
+        self.board.height()
+    }
+
+    /// Returns the [`Player`] whose turn it currently is.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.turn(), Player::P1);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.turn(), Player::P2);
+    ///
+    /// game.play(1)?;
+    /// assert_eq!(game.turn(), Player::P1);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn turn(&self) -> Player {
+        if self.num_moves().is_multiple_of(2) {
+            Player::P1
+        } else {
+            Player::P2
+        }
+    }
+
+    /// Returns a bitboard of the pieces belonging to the current player, i.e. the player returned by [`turn`](Game::turn).
+    ///
+    /// Each column occupies [`HEIGHT`] + 1 bits starting from the least significant bit, with the bottom tile of a column at the lowest bit and an empty sentinel bit above the top tile.
+    /// The tile at `(col, row)` is therefore represented by the bit at index `col * (HEIGHT + 1) + row`.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, HEIGHT};
+    ///
+    /// let game = Game::from_str("445")?;
+    /// let (col, row) = (3, 1);
+    /// let mask = 1 << (col * (HEIGHT + 1) + row);
+    ///
+    /// // Player 2 is to move and owns the piece at (3, 1)
+    /// assert_ne!(game.player_bitboard() & mask, 0);
+    /// assert_eq!(game.opponent_bitboard() & mask, 0);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn player_bitboard(&self) -> u64 {
+        self.board.player_bb()
+    }
+
+    /// Returns a bitboard of the pieces belonging to the opponent of the current player.
+    ///
+    /// The bitboard uses the same layout as [`player_bitboard`](Game::player_bitboard).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, HEIGHT};
+    ///
+    /// let game = Game::from_str("445")?;
+    /// let (col, row) = (3, 0);
+    /// let mask = 1 << (col * (HEIGHT + 1) + row);
+    ///
+    /// // Player 1 played the first piece at (3, 0)
+    /// assert_ne!(game.opponent_bitboard() & mask, 0);
+    /// assert_eq!(game.player_bitboard() & mask, 0);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn opponent_bitboard(&self) -> u64 {
+        self.board.opponent_bb()
+    }
+
+    /// Returns the number of moves made in the game, including any [pop outs](Game::pop_out).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.num_moves(), 0);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.num_moves(), 1);
+    ///
+    /// game.play_slice(&[0, 4, 6, 3])?;
+    /// assert_eq!(game.num_moves(), 5);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn num_moves(&self) -> u8 {
+        self.board.num_moves()
+    }
+
+    /// Returns the number of plies (moves by either player) made in the game, which is the same as [`num_moves`](Game::num_moves).
+    ///
+    /// The ply is 0 before player 1's first move, so it is also the 0-indexed number of the next move to be played.
+    pub fn ply(&self) -> u8 {
+        self.num_moves()
+    }
+
+    /// Returns the 1-indexed number of the current round, where each round consists of a move by player 1 followed by a move by player 2.
+    ///
+    /// The round number starts at 1 and increases after player 2 moves, so use [`turn`](Game::turn) to find which player is to move in the round.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!((game.ply(), game.full_move_number(), game.turn()), (0, 1, Player::P1));
+    ///
+    /// game.play(3)?;
+    /// assert_eq!((game.ply(), game.full_move_number(), game.turn()), (1, 1, Player::P2));
+    ///
+    /// game.play(3)?;
+    /// assert_eq!((game.ply(), game.full_move_number(), game.turn()), (2, 2, Player::P1));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn full_move_number(&self) -> u8 {
+        self.num_moves() / 2 + 1
+    }
+
+    /// Returns a key that uniquely identifies the position on the board, regardless of the order of moves that led to it.
+    ///
+    /// Keys are only unique among games with the same dimensions.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let a = Game::from_str("4453")?;
+    /// let b = Game::from_str("5344")?;
+    ///
+    /// assert_ne!(a, b); // different move histories
+    /// assert_eq!(a.position_key(), b.position_key());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn position_key(&self) -> u64 {
+        self.board.key()
+    }
+
+    /// Returns a Zobrist hash of the position on the board, regardless of the order of moves that led to it.
+    ///
+    /// Unlike [`position_key`](Game::position_key), the hash is updated incrementally as moves are played and undone, and its bits are evenly distributed,
+    /// but different positions are only unlikely, not guaranteed, to have different hashes.
+    ///
+    /// The hash is informational only, such as for keying an external table of positions: the [`Engine`](crate::Engine) does not use it,
+    /// since its transposition tables need keys that never collide, so enabling the `zobrist` feature does not change any scores.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let a = Game::from_str("4453")?;
+    /// let b = Game::from_str("5344")?;
+    ///
+    /// assert_eq!(a.zobrist_key(), b.zobrist_key());
+    /// assert_ne!(a.zobrist_key(), Game::from_str("4435")?.zobrist_key());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    #[cfg(feature = "zobrist")]
+    pub fn zobrist_key(&self) -> u64 {
+        self.board.zobrist()
+    }
+
+    /// Checks if two games have the same position on the board, regardless of the order of moves that led to it.
+    ///
+    /// This differs from `==`, which only considers games equal if they also have the same move history.
+    /// Games with different dimensions never have the same position.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let a = Game::from_str("4453")?;
+    /// let b = Game::from_str("5344")?;
+    /// let c = Game::from_matrix(a.matrix())?;
+    ///
+    /// assert_ne!(a, b); // different move histories
+    /// assert!(a.same_position(&b));
+    /// assert!(a.same_position(&c));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn same_position(&self, other: &Game) -> bool {
+        self.board == other.board
+    }
+
+    /// Returns a key that is shared by the position and its horizontal mirror image, so mirror-image positions can be treated as one.
+    ///
+    /// The key is the smaller of the [`position_key`](Game::position_key) of the position and of its mirror image.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let a = Game::from_str("1123")?;
+    /// let b = Game::from_str("7765")?;
+    ///
+    /// assert_ne!(a.position_key(), b.position_key());
+    /// assert_eq!(a.canonical_key(), b.canonical_key());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn canonical_key(&self) -> u64 {
+        self.position_key().min(self.board.mirror().key())
+    }
+
+    /// Checks if the position is identical to its horizontal mirror image.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert!(Game::new().is_symmetric());
+    /// assert!(Game::from_str("441177")?.is_symmetric());
+    /// assert!(!Game::from_str("45")?.is_symmetric());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn is_symmetric(&self) -> bool {
+        self.board.mirror() == self.board
+    }
+
+    /// Returns a slice of all the moves played in the game as 0-indexed columns.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.moves(), &[]);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.moves(), &[3]);
+    ///
+    /// game.play_slice(&[0, 4, 6, 3])?;
+    /// assert_eq!(game.moves(), &[3, 0, 4, 6, 3]);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn moves(&self) -> &[u8] {
+        &self.moves
+    }
+
+    /// Returns the 0-indexed column of the last move played, or [`None`] if the move history is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert_eq!(Game::new().last_move(), None);
+    /// assert_eq!(Game::from_str("4453")?.last_move(), Some(2));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn last_move(&self) -> Option<u8> {
+        self.moves.last().copied()
+    }
+
+    /// Returns the `(x, y)` coordinates of the tile that the piece of the last move landed in, or [`None`] if the move history is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert_eq!(Game::new().last_move_coord(), None);
+    /// assert_eq!(Game::from_str("4454")?.last_move_coord(), Some((3, 2)));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn last_move_coord(&self) -> Option<(u8, u8)> {
+        let col = self.last_move()?;
+        Some((col, self.board.pieces_in_col(col) - 1))
+    }
+
+    /// Returns the moves played in the game in order, including the player who played each move and the row that each piece landed in.
+    ///
+    /// Like [`moves`](Game::moves), this only includes moves that were played after the game was created.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, MoveRecord, Player};
+    ///
+    /// let game = Game::from_str("445")?;
+    /// assert_eq!(
+    ///     game.move_history(),
+    ///     vec![
+    ///         MoveRecord { player: Player::P1, col: 3, row: 0 },
+    ///         MoveRecord { player: Player::P2, col: 3, row: 1 },
+    ///         MoveRecord { player: Player::P1, col: 4, row: 0 },
+    ///     ]
+    /// );
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn move_history(&self) -> Vec<MoveRecord> {
+        // Undo the moves from the current position, so that pieces placed before the moves are accounted for
+        let mut heights = self.column_heights();
+        let mut player = self.turn();
+
+        let mut history: Vec<MoveRecord> = self
+            .moves
+            .iter()
+            .rev()
+            .map(|&col| {
+                player = !player;
+                heights[usize::from(col)] -= 1;
+                MoveRecord {
+                    player,
+                    col,
+                    row: heights[usize::from(col)],
+                }
+            })
+            .collect();
+
+        history.reverse();
+        history
+    }
+
+    /// Returns a string of all the moves played in the game as 1-indexed columns.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.moves_str(), "");
+    ///
+    /// game.play_str("1234567")?;
+    /// assert_eq!(game.moves_str(), "1234567");
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn moves_str(&self) -> String {
+        let mut s = String::with_capacity(self.num_moves().into());
+        for col in self.moves() {
+            s.push(char::from_digit((col + 1).into(), 10).unwrap());
+        }
+        s
+    }
+
+    /// Returns a [`GameTranscript`] of the game's moves and its current [`status`](Game::status) as the result, without any other metadata.
+    ///
+    /// Returns [`None`] if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`],
+    /// or if the move history does not start from an empty board, such as for games created with [`from_matrix`](Game::from_matrix).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player, Status};
+    ///
+    /// let game = Game::from_str("4122343344")?;
+    /// let transcript = game.transcript().unwrap();
+    ///
+    /// assert_eq!(transcript.moves, game.moves());
+    /// assert_eq!(transcript.result, Some(Status::Win(Player::P2)));
+    /// assert_eq!(transcript.to_string(), "[Result \"0-1\"]\n4122343344");
+    ///
+    /// let copy = Game::from_matrix(game.matrix())?;
+    /// assert_eq!(copy.transcript(), None);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn transcript(&self) -> Option<GameTranscript> {
+        if !self.is_standard_from_start() {
+            return None;
+        }
+
+        Some(GameTranscript {
+            moves: self.moves.clone(),
+            result: Some(self.status()),
+            ..GameTranscript::default()
+        })
+    }
+
+    /// Returns the name of the opening that the game starts with, or [`None`] if the first moves do not match a named opening.
+    ///
+    /// Only the first moves of the game are checked against a small built-in table, and the moves played after the opening are ignored.
+    /// When several openings match, the longest one is returned. The mirror image of an opening has the same name.
+    ///
+    /// | Moves | Name                            |
+    /// |-------|---------------------------------|
+    /// | 1     | Edge opening                    |
+    /// | 2     | Flank opening                   |
+    /// | 3     | Off-center opening              |
+    /// | 4     | Center opening                  |
+    /// | 41    | Center opening, edge reply      |
+    /// | 42    | Center opening, flank reply     |
+    /// | 43    | Center opening, adjacent reply  |
+    /// | 44    | Center opening, follow-up reply |
+    /// | 444   | Center tower                    |
+    ///
+    /// Games without the standard dimensions of [`WIDTH`] x [`HEIGHT`] and [`CONNECT_LENGTH`], and games whose move history does not start
+    /// from an empty board, such as ones created with [`from_matrix`](Game::from_matrix) or continued after a [pop out](Game::pop_out), have no opening name.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert_eq!(Game::from_str("4")?.opening_name(), Some("Center opening"));
+    /// assert_eq!(Game::from_str("4526")?.opening_name(), Some("Center opening, adjacent reply"));
+    /// assert_eq!(Game::from_str("7")?.opening_name(), Some("Edge opening"));
+    /// assert_eq!(Game::new().opening_name(), None);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn opening_name(&self) -> Option<&'static str> {
+        if !self.is_standard_from_start() {
+            return None;
+        }
+
+        let moves = self.moves_str();
+        let mirrored: String = moves
+            .bytes()
+            .map(|c| char::from(b'1' + b'0' + WIDTH - c))
+            .collect();
+
+        OPENINGS
+            .iter()
+            .filter(|(opening, _)| moves.starts_with(opening) || mirrored.starts_with(opening))
+            .max_by_key(|(opening, _)| opening.len())
+            .map(|&(_, name)| name)
+    }
+
+    /// Checks if the game has the standard dimensions and connect length, and its move history starts from an empty board.
+    fn is_standard_from_start(&self) -> bool {
+        self.width() == WIDTH
+            && self.height() == HEIGHT
+            && self.connect_length() == CONNECT_LENGTH
+            && self.moves.len() == usize::from(self.num_moves())
+    }
+
+    /// Returns a [`Status`] representing the current state of the game, which is either ongoing or finished.
+    ///
+    /// Use [`outcome`](Game::outcome) to only get the result of a finished game.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player, Status};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.status(), Status::Ongoing);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.status(), Status::Ongoing);
+    ///
+    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
+    /// assert_eq!(game.status(), Status::Win(Player::P1));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn status(&self) -> Status {
+        if let Some(player) = self.winner {
+            Status::Win(player)
+        } else if self.board.is_full() {
+            Status::Draw
+        } else {
+            Status::Ongoing
+        }
+    }
+
+    /// Returns the [`Outcome`] of the game if it is over, or [`None`] if the game is still ongoing.
+    ///
+    /// This is the same as [`status`](Game::status) without the ongoing state.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Outcome, Player};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.outcome(), None);
+    ///
+    /// game.play_slice(&[3, 2, 3, 2, 3, 2, 3])?;
+    /// assert_eq!(game.outcome(), Some(Outcome::Win(Player::P1)));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn outcome(&self) -> Option<Outcome> {
+        self.status().outcome()
+    }
+
+    /// Checks if the game is over and no more moves can be played.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::new();
+    /// assert!(!game.is_over());
+    ///
+    /// game.play(3)?;
+    /// assert!(!game.is_over());
+    ///
+    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
+    /// assert!(game.is_over());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn is_over(&self) -> bool {
+        self.winner.is_some() || self.board.is_full()
+    }
+
+    /// Returns the bitboard of the winner's pieces, or [`None`] if there is no winner.
+    ///
+    /// The player who just moved usually wins, but a [pop out](Game::pop_out) can also complete lines for the player to move.
+    fn winner_bb(&self) -> Option<u64> {
+        if self.winner? == self.turn() {
+            Some(self.board.player_bb())
+        } else {
+            Some(self.board.opponent_bb())
+        }
+    }
+
+    /// Returns the winner along with a bitboard of the first tiles of their winning lines in one direction.
+    fn winning_lines(&self) -> Option<(Player, u64, Direction)> {
+        let (lines, direction) = self.board.check_win(self.winner_bb()?)?;
+        Some((self.winner?, lines, direction))
+    }
+
+    /// Checks if the game can only end in a draw, because every possible winning line on the board contains pieces of both players.
+    ///
+    /// This can be detected before the board is full, which is when the [`status`](Game::status) of the game becomes [`Status::Draw`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4455")?;
+    /// assert!(!game.is_dead_draw());
+    ///
+    /// let game = Game::from_str("111111222222333333544444455555666666777777")?;
+    /// assert!(game.is_dead_draw());
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn is_dead_draw(&self) -> bool {
+        self.board.is_dead_draw()
+    }
+
+    /// Returns an array containing the `(x, y)` coordinates of four pieces that form a winning line horizontally, vertically, or diagonally. If no line exists (there is no winner), then [`None`] is returned.
+    ///
+    /// The order of coordinates is sorted. If there are multiple winning lines, then any one of the lines can be returned.
+    /// In games that need more than four pieces in a row to win, the first four pieces of the line are returned,
+    /// and [`win_line_coords`](Game::win_line_coords) returns the whole line.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.win_coords(), None);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.win_coords(), None);
+    ///
+    /// game.play_slice(&[2, 3, 2, 3, 2, 3])?;
+    /// assert_eq!(game.win_coords(), Some([(3, 0), (3, 1), (3, 2), (3, 3)]));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn win_coords(&self) -> Option<[(u8, u8); 4]> {
+        let coords = self.win_line_coords()?;
+        Some(array::from_fn(|i| coords[i]))
+    }
+
+    /// Returns the `(x, y)` coordinates of every piece in a winning line like [`win_coords`](Game::win_coords),
+    /// with [`connect_length`](Game::connect_length) coordinates instead of always four. If there is no winner, then [`None`] is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let mut game = Game::with_connect_length(9, 6, 5);
+    /// game.play_slice(&[0, 0, 1, 1, 2, 2, 3, 3, 4])?;
+    /// assert_eq!(
+    ///     game.win_line_coords(),
+    ///     Some(vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)])
+    /// );
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn win_line_coords(&self) -> Option<Vec<(u8, u8)>> {
+        self.win_line().map(|line| line.coords)
+    }
+
+    /// Returns a [`WinLine`] describing the coordinates, orientation, and owner of a winning line. If no line exists (there is no winner), then [`None`] is returned.
+    ///
+    /// If there are multiple winning lines, then any one of the lines can be returned, consistent with [`win_coords`](Game::win_coords).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Direction, Game, Player};
+    ///
+    /// let game = Game::from_str("4122343344")?;
+    /// let line = game.win_line().unwrap();
+    ///
+    /// assert_eq!(line.coords, vec![(0, 0), (1, 1), (2, 2), (3, 3)]);
+    /// assert_eq!(line.direction, Direction::AscendingDiagonal);
+    /// assert_eq!(line.player, Player::P2);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn win_line(&self) -> Option<WinLine> {
+        let (player, bitboard, direction) = self.winning_lines()?;
+        let index = u8::try_from(bitboard.trailing_zeros()).unwrap();
+
+        Some(WinLine {
+            coords: self.line_coords(index, direction),
+            direction,
+            player,
+        })
+    }
+
+    /// Returns the `(x, y)` coordinates of the piece that completed the winning line, which is the piece placed by the last move.
+    /// If there is no winner, or the game has no move history such as when created with [`from_matrix`](Game::from_matrix), then [`None`] is returned.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4122343344")?;
+    /// assert_eq!(game.winning_move_coord(), Some((3, 3)));
+    ///
+    /// let copy = Game::from_matrix(game.matrix())?;
+    /// assert_eq!(copy.winning_move_coord(), None);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn winning_move_coord(&self) -> Option<(u8, u8)> {
+        self.board.opponent_winning_bb()?;
+        self.last_move_coord()
+    }
+
+    /// Returns the coordinates of every distinct line of [`connect_length`](Game::connect_length) pieces that belong to the winner.
+    /// If there is no winner, then the vector is empty.
+    ///
+    /// Each line has sorted coordinates, and the lines themselves are sorted. A longer line counts as multiple overlapping lines.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// // _ _ _ _ _ _ _
+    /// // _ _ _ _ _ _ _
+    /// // _ _ _ _ _ _ _
+    /// // _ _ _ _ _ _ _
+    /// // O O _ O O _ _
+    /// // X X X X X _ _
+    /// let game = Game::from_str("112244553")?;
+    /// assert_eq!(
+    ///     game.all_win_coords(),
+    ///     vec![
+    ///         vec![(0, 0), (1, 0), (2, 0), (3, 0)],
+    ///         vec![(1, 0), (2, 0), (3, 0), (4, 0)],
+    ///     ],
+    /// );
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn all_win_coords(&self) -> Vec<Vec<(u8, u8)>> {
+        let Some(bitboard) = self.winner_bb() else {
+            return Vec::new();
+        };
+        let mut lines: Vec<_> =
+            bitboard::line_masks(self.width(), self.height(), self.connect_length())
+                .iter()
+                .filter(|&&mask| bitboard & mask == mask)
+                .map(|&mask| self.mask_coords(mask))
+                .collect();
+
+        lines.sort_unstable();
+        lines
+    }
+
+    /// Returns the sorted coordinates of the tiles in a line mask.
+    fn mask_coords(&self, mut mask: u64) -> Vec<(u8, u8)> {
+        let mut coords = Vec::with_capacity(mask.count_ones() as usize);
+        while mask != 0 {
+            let index = u8::try_from(mask.trailing_zeros()).unwrap();
+            mask &= mask - 1;
+            coords.push((index / (self.height() + 1), index % (self.height() + 1)));
+        }
+        coords
+    }
+
+    /// Returns the sorted coordinates of the tiles of a line in a given direction, starting from the tile with the lowest bitboard index.
+    fn line_coords(&self, index: u8, direction: Direction) -> Vec<(u8, u8)> {
+        let start_col = index / (self.height() + 1);
+        let start_row = index % (self.height() + 1);
+
+        // A descending line below this row would continue into the empty bit above a column, so its first tile is always high enough
+        debug_assert!(
+            direction != Direction::DescendingDiagonal || start_row >= self.connect_length() - 1,
+            "line_coords: descending diagonal starts too low"
+        );
+
+        (0..self.connect_length())
+            .map(|i| match direction {
+                Direction::AscendingDiagonal => (start_col + i, start_row + i),
+                Direction::DescendingDiagonal => (start_col + i, start_row - i),
+                Direction::Horizontal => (start_col + i, start_row),
+                Direction::Vertical => (start_col, start_row + i),
+            })
+            .collect()
+    }
+
+    /// Returns the [`Player`] who owns the piece at `(x, y)`, or [`None`] if the tile is empty.
+    ///
+    /// # Panics
+    /// Panics if given a coordinate that is out of bounds (x >= [`width`](Game::width) or y >= [`height`](Game::height)).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player};
+    ///
+    /// let mut game = Game::new();
+    /// game.play(0)?;
+    /// game.play(1)?;
+    ///
+    /// assert_eq!(game.at(0, 0), Some(Player::P1));
+    /// assert_eq!(game.at(1, 0), Some(Player::P2));
+    /// assert_eq!(game.at(2, 0), None);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    ///
+    /// Passing a coordinate that is out of bounds, causing a panic:
+    /// ```should_panic
+    /// # use connect_four_engine::Game;
+    /// # let game = Game::new();
+    /// let _ = game.at(7, 0); // this panics
+    /// ```
+    pub fn at(&self, x: u8, y: u8) -> Option<Player> {
+        let (width, height) = (self.width(), self.height());
+        assert!(x < width, "at: x is out of bounds (maximum {width})");
+        assert!(y < height, "at: y is out of bounds (maximum {height})");
+
+        let turn = self.turn();
+        let offset = x * (height + 1) + y;
+        let mask = 1 << offset;
+
+        if self.board.player_bb() & mask != 0 {
+            Some(turn)
+        } else if self.board.opponent_bb() & mask != 0 {
+            Some(!turn)
+        } else {
+            None
+        }
+    }
+
+    /// Returns the current state of the game as a 2D array where each element is [`Some(Player)`] if that player owns a piece at the location or [`None`] if the tile is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player};
+    ///
+    /// let mut game = Game::new();
+    /// assert_eq!(game.matrix()[0][3], None);
+    ///
+    /// game.play(3)?;
+    /// assert_eq!(game.matrix()[0][3], Some(Player::P1));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    /// The 2D array is in row-major order:
+    /// ```
+    /// # use connect_four_engine::Game;
+    /// # let game = Game::from_str("111112222233333144444255555376666667777754")?;
+    /// # let x = 0;
+    /// # let y = 5;
+    /// let a = game.matrix()[y][x];
+    /// # let x = 0;
+    /// # let y = 5;
+    /// let b = game.at(x, y);
+    /// assert_eq!(a, b);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`]. Use [`grid`](Game::grid) for other dimensions.
+    pub fn matrix(&self) -> [[Option<Player>; WIDTH as usize]; HEIGHT as usize] {
+        assert!(
+            self.width() == WIDTH && self.height() == HEIGHT,
+            "matrix: game does not have standard dimensions"
+        );
+
+        array::from_fn(|y| {
+            array::from_fn(|x| self.at(x.try_into().unwrap(), y.try_into().unwrap()))
+        })
+    }
+
+    /// Returns the position encoded as three planes of features for machine learning, each in the same row-major order as [`matrix`](Game::matrix),
+    /// where the first row is the bottom row of the board.
+    ///
+    /// - Plane 0 is 1.0 where the current player has a piece, and 0.0 elsewhere.
+    /// - Plane 1 is 1.0 where the opponent has a piece, and 0.0 elsewhere.
+    /// - Plane 2 is all 1.0 when it is player 1's turn, and all 0.0 when it is player 2's turn.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4")?;
+    /// let planes = game.feature_planes();
+    ///
+    /// assert_eq!(planes[0][0][3], 0.0);
+    /// assert_eq!(planes[1][0][3], 1.0);
+    /// assert_eq!(planes[2][0][0], 0.0);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn feature_planes(&self) -> [[[f32; WIDTH as usize]; HEIGHT as usize]; 3] {
+        let matrix = self.matrix();
+        let turn = self.turn();
+        let plane = |f: &dyn Fn(Option<Player>) -> bool| {
+            matrix.map(|row| row.map(|tile| if f(tile) { 1.0 } else { 0.0 }))
+        };
+
+        [
+            plane(&|tile| tile == Some(turn)),
+            plane(&|tile| tile == Some(!turn)),
+            plane(&|_| turn == Player::P1),
+        ]
+    }
+
+    /// Returns the current state of the game as a 2D vector in row-major order, which works for boards of any dimensions.
+    /// Each element is [`Some(Player)`] if that player owns a piece at the location or [`None`] if the tile is empty.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, Player};
+    ///
+    /// let mut game = Game::with_dimensions(8, 7);
+    /// game.play(7)?;
+    ///
+    /// let grid = game.grid();
+    /// assert_eq!(grid.len(), 7);
+    /// assert_eq!(grid[0].len(), 8);
+    /// assert_eq!(grid[0][7], Some(Player::P1));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn grid(&self) -> Vec<Vec<Option<Player>>> {
+        (0..self.height())
+            .map(|y| (0..self.width()).map(|x| self.at(x, y)).collect())
+            .collect()
+    }
+
+    /// Returns the board as a string in the same format as [`Display`](fmt::Display), optionally coloring the pieces of each player with ANSI escape codes for terminals.
+    ///
+    /// Player 1's pieces are red and player 2's pieces are yellow. The uncolored string is the same as [`to_string`](ToString::to_string).
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("44")?;
+    /// assert_eq!(game.render(false), game.to_string());
+    /// assert!(game.render(true).ends_with("_ _ _ \x1b[31mX\x1b[0m _ _ _"));
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn render(&self, colored: bool) -> String {
+        let text = self.to_string();
+        if !colored {
+            return text;
+        }
+
+        let mut colored_text = String::with_capacity(text.len() * 2);
+        for c in text.chars() {
+            match c {
+                'X' => colored_text.push_str("\x1b[31mX\x1b[0m"),
+                'O' => colored_text.push_str("\x1b[33mO\x1b[0m"),
+                _ => colored_text.push(c),
+            }
+        }
+        colored_text
+    }
+
+    /// Returns the board as a self-contained SVG image, with red discs for player 1's pieces, yellow discs for player 2's pieces, and white empty slots on a blue board.
+    ///
+    /// # Panics
+    /// Panics if the game does not have the standard dimensions of [`WIDTH`] x [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4453")?;
+    /// let svg = game.to_svg();
+    ///
+    /// assert!(svg.starts_with("<svg"));
+    /// assert_eq!(svg.matches("fill=\"red\"").count(), 2);
+    /// assert_eq!(svg.matches("<circle").count(), 42);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    #[cfg(feature = "svg")]
+    pub fn to_svg(&self) -> String {
+        use fmt::Write;
+
+        let width = usize::from(WIDTH) * SVG_TILE_SIZE;
+        let height = usize::from(HEIGHT) * SVG_TILE_SIZE;
+        let mut svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"{width}\" height=\"{height}\" viewBox=\"0 0 {width} {height}\">\
+             <rect width=\"{width}\" height=\"{height}\" fill=\"#1e4fbf\"/>"
+        );
+
+        for (y, row) in self.matrix().iter().enumerate() {
+            for (x, tile) in row.iter().enumerate() {
+                let fill = match tile {
+                    Some(Player::P1) => "red",
+                    Some(Player::P2) => "yellow",
+                    None => "white",
+                };
+                let cx = x * SVG_TILE_SIZE + SVG_TILE_SIZE / 2;
+                let cy = height - y * SVG_TILE_SIZE - SVG_TILE_SIZE / 2;
+                let r = SVG_TILE_SIZE * 2 / 5;
+                write!(
+                    svg,
+                    "<circle cx=\"{cx}\" cy=\"{cy}\" r=\"{r}\" fill=\"{fill}\"/>"
+                )
+                .expect("writing to a string should not fail");
+            }
+        }
+
+        svg.push_str("</svg>");
+        svg
+    }
+
+    /// Returns the board as a string with 1-indexed column labels above the grid, which is the same as formatting the game with `{:#}`.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4453")?;
+    /// let expected = "\
+    /// 1 2 3 4 5 6 7
+    /// _ _ _ _ _ _ _
+    /// _ _ _ _ _ _ _
+    /// _ _ _ _ _ _ _
+    /// _ _ _ _ _ _ _
+    /// _ _ _ O _ _ _
+    /// _ _ O X X _ _";
+    ///
+    /// assert_eq!(game.display_with_labels(), expected);
+    /// assert_eq!(format!("{game:#}"), expected);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn display_with_labels(&self) -> String {
+        format!("{self:#}")
+    }
+
+    /// Returns the board as Unicode text art, with a box-drawing frame around the grid and 1-indexed column labels underneath.
+    ///
+    /// Player 1's pieces are `●`, player 2's pieces are `○`, and empty tiles are `·`. No colors are added.
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// let game = Game::from_str("4453")?;
+    /// let expected = "\
+    /// │ · · · · · · · │
+    /// │ · · · · · · · │
+    /// │ · · · · · · · │
+    /// │ · · · · · · · │
+    /// │ · · · ○ · · · │
+    /// │ · · ○ ● ● · · │
+    /// └───────────────┘
+    ///   1 2 3 4 5 6 7";
+    ///
+    /// assert_eq!(game.to_unicode(), expected);
+    /// # Ok::<(), connect_four_engine::MoveError>(())
+    /// ```
+    pub fn to_unicode(&self) -> String {
+        // Labels with multiple digits widen every column
+        let tile_width = self.width().to_string().len();
+        let fmt_row = |tiles: Vec<String>| {
+            tiles
+                .iter()
+                .map(|tile| format!("{tile:>tile_width$}"))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut lines: Vec<String> = (0..self.height())
+            .rev()
+            .map(|y| {
+                let tiles = (0..self.width())
+                    .map(|x| match self.at(x, y) {
+                        Some(Player::P1) => "●".to_string(),
+                        Some(Player::P2) => "○".to_string(),
+                        None => "·".to_string(),
+                    })
+                    .collect();
+                format!("│ {} │", fmt_row(tiles))
+            })
+            .collect();
+
+        let inner_width = usize::from(self.width()) * (tile_width + 1) + 1;
+        lines.push(format!("└{}┘", "─".repeat(inner_width)));
+
+        let labels = (1..=self.width()).map(|col| col.to_string()).collect();
+        lines.push(format!("  {}", fmt_row(labels)));
+        lines.join("\n")
+    }
+
+    /// Returns the number of unique game positions at a specific depth.
+    ///
+    /// # Warning
+    /// Running this at a large depth (>14) is computationally expensive.
+    ///
+    /// # Panics
+    /// Panics if given a depth larger than [`WIDTH`] * [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert_eq!(Game::perft(0), 1);
+    /// assert_eq!(Game::perft(1), 7);
+    /// assert_eq!(Game::perft(2), 49);
+    /// assert_eq!(Game::perft(3), 238);
+    /// ```
+    ///
+    /// Passing a large depth, causing a panic:
+    /// ```should_panic
+    /// use connect_four_engine::Game;
+    /// Game::perft(43); // this panics
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn perft(depth: u8) -> u64 {
+        assert!(depth <= AREA, "perft: depth is too high (maximum {})", AREA);
+        let board = Board::new();
+        let mut nodes = 0;
+        Self::visit_unique(
+            board,
+            depth,
+            Board::key,
+            &mut HashSet::from([board.key()]),
+            &mut |_| nodes += 1,
+        );
+        nodes
+    }
+
+    /// Returns statistics about every sequence of moves with a specific depth, counting transpositions separately unlike [`perft`](Game::perft).
+    ///
+    /// Games that end before reaching the depth are not counted.
+    ///
+    /// # Warning
+    /// The number of sequences grows exponentially, so running this at a large depth (>9) is computationally expensive.
+    ///
+    /// # Panics
+    /// Panics if given a depth larger than [`WIDTH`] * [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, PerftStats};
+    ///
+    /// assert_eq!(Game::perft_detailed(3).nodes, 343);
+    /// assert_eq!(
+    ///     Game::perft_detailed(7),
+    ///     PerftStats {
+    ///         nodes: 823_536,
+    ///         terminal_wins: 13_032,
+    ///         terminal_draws: 0,
+    ///     }
+    /// );
+    /// ```
+    pub fn perft_detailed(depth: u8) -> PerftStats {
+        assert!(
+            depth <= AREA,
+            "perft_detailed: depth is too high (maximum {})",
+            AREA
+        );
+        let mut stats = PerftStats::default();
+        Self::count_paths(Board::new(), depth, &mut stats);
+        stats
+    }
+
+    /// Helper function for perft_detailed.
+    fn count_paths(board: Board, depth: u8, stats: &mut PerftStats) {
+        if depth == 0 {
+            stats.nodes += 1;
+            if board.has_opponent_won() {
+                stats.terminal_wins += 1;
+            } else if board.is_full() {
+                stats.terminal_draws += 1;
+            }
+            return;
+        }
+
+        if board.is_terminal() {
+            return;
+        }
+
+        for i in 0..WIDTH {
+            if board.is_open(i) {
+                let mut new_board = board;
+                new_board.play_unchecked(i);
+                Self::count_paths(new_board, depth - 1, stats);
+            }
+        }
+    }
+
+    /// Returns the number of unique game positions at a specific depth like [`perft`](Game::perft), using an explicit stack instead of recursion.
+    ///
+    /// If `max_seen` is given, the set of visited positions is cleared whenever it reaches that size, which bounds memory usage.
+    /// Clearing the set causes some positions to be counted more than once, so the result is then an upper bound rather than an exact count.
+    ///
+    /// # Warning
+    /// Running this at a large depth (>14) is computationally expensive.
+    ///
+    /// # Panics
+    /// Panics if given a depth larger than [`WIDTH`] * [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::Game;
+    ///
+    /// assert_eq!(Game::perft_iter(4, None), Game::perft(4));
+    /// assert!(Game::perft_iter(4, Some(100)) >= Game::perft(4));
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn perft_iter(depth: u8, max_seen: Option<usize>) -> u64 {
+        assert!(
+            depth <= AREA,
+            "perft_iter: depth is too high (maximum {})",
+            AREA
+        );
+
+        let board = Board::new();
+        let mut seen = HashSet::from([board.key()]);
+        let mut stack = vec![(board, depth)];
+        let mut nodes = 0;
+
+        while let Some((board, depth)) = stack.pop() {
+            if depth == 0 {
+                nodes += 1;
+                continue;
+            }
+
+            if board.is_terminal() {
+                continue;
+            }
+
+            for i in 0..WIDTH {
+                if board.is_open(i) {
+                    let mut new_board = board;
+                    new_board.play_unchecked(i);
+
+                    if max_seen.is_some_and(|max_seen| seen.len() >= max_seen) {
+                        seen.clear();
+                    }
+
+                    if seen.insert(new_board.key()) {
+                        stack.push((new_board, depth - 1));
+                    }
+                }
+            }
+        }
+
+        nodes
+    }
+
+    /// Returns counts of the distinct positions where the game ends within `max_depth` moves,
+    /// along with the distinct positions at `max_depth` where the game is still ongoing.
+    ///
+    /// Positions reached through different orders of moves are only counted once, like [`perft`](Game::perft),
+    /// so `ongoing` plus the finished positions with exactly `max_depth` moves is equal to `Game::perft(max_depth)`.
+    ///
+    /// # Warning
+    /// Running this at a large depth (>14) is computationally expensive.
+    ///
+    /// # Panics
+    /// Panics if given a depth larger than [`WIDTH`] * [`HEIGHT`].
+    ///
+    /// # Examples
+    /// ```
+    /// use connect_four_engine::{Game, TerminalStats};
+    ///
+    /// assert_eq!(
+    ///     Game::terminal_stats(3),
+    ///     TerminalStats { p1_wins: 0, p2_wins: 0, draws: 0, ongoing: 238 }
+    /// );
+    /// assert_eq!(Game::terminal_stats(7).p1_wins, 728);
+    /// ```
+    #[cfg(feature = "std")]
+    pub fn terminal_stats(max_depth: u8) -> TerminalStats {
+        assert!(
+            max_depth <= AREA,
+            "terminal_stats: depth is too high (maximum {})",
+            AREA
+        );
+
+        let board = Board::new();
+        let mut seen = HashSet::from([board.key()]);
+        let mut stats = TerminalStats::default();
+        Self::count_terminal(board, max_depth, &mut seen, &mut stats);
+        stats
+    }
+
+    /// Helper function for terminal_stats.
+    #[cfg(feature = "std")]
+    fn count_terminal(board: Board, depth: u8, seen: &mut HashSet<u64>, stats: &mut TerminalStats) {
+        if depth == 0 {
+            stats.ongoing += 1;
+            return;
+        }
+
+        for i in 0..WIDTH {
+            if !board.is_open(i) {
+                continue;
+            }
+
+            let mut new_board = board;
+            let won =
+                new_board.play_and_check(board.possible_bb() & bitboard::column_mask(i, HEIGHT));
+            if !seen.insert(new_board.key()) {
+                continue;
+            }
+
+            if won {
+                if board.num_moves().is_multiple_of(2) {
+                    stats.p1_wins += 1;
+                } else {
+                    stats.p2_wins += 1;
+                }
+            } else if new_board.is_full() {
+                stats.draws += 1;
+            } else {
+                Self::count_terminal(new_board, depth - 1, seen, stats);
+            }
+        }
+    }
+
+    /// Helper function for perft and unique_positions, which calls `visit` on every position a specific number of moves after a board.
+    /// Positions are skipped if their key is already in `seen`, so transpositions are only visited once.
+    #[cfg(feature = "std")]
+    fn visit_unique<K: Eq + Hash>(
+        board: Board,
+        depth: u8,
+        key: fn(&Board) -> K,
+        seen: &mut HashSet<K>,
+        visit: &mut impl FnMut(Board),
+    ) {
+        if depth == 0 {
+            visit(board);
+            return;
+        }
+
+        if board.is_terminal() {
+            return;
+        }
+
+        for i in 0..board.width() {
+            if board.is_open(i) {
+                let mut new_board = board;
+                new_board.play_unchecked(i);
+                if seen.insert(key(&new_board)) {
+                    Self::visit_unique(new_board, depth - 1, key, seen, visit);
                 }
             }
         }
+    }
+
+    /// Returns the unique ongoing positions a specific number of moves after a board, where positions with the same key are only included once.
+    #[cfg(feature = "std")]
+    pub(crate) fn unique_positions(board: Board, depth: u8, key: fn(&Board) -> u128) -> Vec<Board> {
+        let mut positions = Vec::new();
+        Self::visit_unique(board, depth, key, &mut HashSet::new(), &mut |board| {
+            if !board.is_terminal() {
+                positions.push(board);
+            }
+        });
+        positions
+    }
+}
+
+/// Hashes the position on the board using [`position_key`](Game::position_key), so transpositions of the same position share a hash.
+///
+/// This is consistent with [`PartialEq`], which additionally compares the move history.
+impl Hash for Game {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.position_key().hash(state);
+    }
+}
+
+impl FromStr for Game {
+    type Err = MoveError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::from_str(s)
+    }
+}
+
+impl fmt::Display for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut grid = self.grid();
+        grid.reverse();
+
+        // Labels with multiple digits widen every column when formatting with `{:#}`
+        let tile_width = if f.alternate() {
+            self.width().to_string().len()
+        } else {
+            1
+        };
+
+        let fmt_tile = |tile: Option<Player>| {
+            let tile = tile.map_or("_".into(), |player| player.to_string());
+            format!("{tile:>tile_width$}")
+        };
+
+        let mut rows: Vec<String> = grid
+            .into_iter()
+            .map(|row| row.into_iter().map(fmt_tile).collect::<Vec<_>>().join(" "))
+            .collect();
+
+        if f.alternate() {
+            let labels: Vec<String> = (1..=self.width())
+                .map(|col| format!("{col:>tile_width$}"))
+                .collect();
+            rows.insert(0, labels.join(" "));
+        }
+
+        write!(f, "{}", rows.join("\n"))
+    }
+}
+
+impl fmt::Binary for Game {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let bitboards = if self.turn() == Player::P1 {
+            (self.board.player_bb(), self.board.opponent_bb())
+        } else {
+            (self.board.opponent_bb(), self.board.player_bb())
+        };
+
+        let (width, height) = (self.width(), self.height());
+
+        // Formatting with `{:#b}` includes the sentinel row for debugging
+        let format = if f.alternate() {
+            bitboard::format_raw
+        } else {
+            bitboard::format
+        };
+
+        writeln!(f, "{:?}", Player::P1)?;
+        writeln!(f, "{}", format(bitboards.0, width, height))?;
+        writeln!(f)?;
+        writeln!(f, "{:?}", Player::P2)?;
+        write!(f, "{}", format(bitboards.1, width, height))
+    }
+}
+
+#[cfg(test)]
+#[allow(clippy::unusual_byte_groupings)]
+mod tests {
+    use super::*;
+    #[cfg(feature = "std")]
+    use std::fs::File;
+    #[cfg(feature = "std")]
+    use std::io::{prelude::*, BufReader};
+    #[cfg(feature = "std")]
+    use std::ops::RangeBounds;
+
+    #[test]
+    fn new_game() {
+        let game = Game::new();
+        assert_eq!(game.board.player_bb(), 0);
+        assert_eq!(game.board.occupied_bb(), 0);
+        assert_eq!(game.num_moves(), 0);
+        assert!(!game.is_over());
+        assert_eq!(game.status(), Status::Ongoing);
+    }
+
+    #[test]
+    fn play_one() -> Result<(), MoveError> {
+        let mut game = Game::new();
+        game.play(3)?;
+        assert_eq!(
+            game.board.player_bb(),
+            0b_0000000_0000000_0000000_0000000_0000000_0000000_0000000
+        );
+        assert_eq!(
+            game.board.occupied_bb(),
+            0b_0000000_0000000_0000000_0000001_0000000_0000000_0000000
+        );
+        assert_eq!(game.num_moves(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn play_multiple() -> Result<(), MoveError> {
+        let mut game1 = Game::new();
+        game1.play_slice(&[3, 3, 3, 3])?;
+
+        let mut game2 = Game::new();
+        game2.play_str("4444")?;
+
+        assert_eq!(
+            game1.board.player_bb(),
+            0b_0000000_0000000_0000000_0000101_0000000_0000000_0000000
+        );
+        assert_eq!(
+            game1.board.occupied_bb(),
+            0b_0000000_0000000_0000000_0001111_0000000_0000000_0000000
+        );
+        assert_eq!(game1.num_moves(), 4);
+        assert_eq!(game1, game2);
+        Ok(())
+    }
+
+    #[test]
+    fn out_of_bounds() {
+        let mut game = Game::new();
+        let result = game.play(7);
+        assert_eq!(result, Err(MoveError::InvalidColumn));
+    }
+
+    #[test]
+    fn full_column() {
+        let mut game = Game::new();
+        let result = game.play_str("1111111");
+        assert_eq!(result, Err(MoveError::ColumnFull));
+    }
+
+    #[test]
+    fn play_str_lenient() -> Result<(), MoveError> {
+        let expected = Game::from_str("321")?;
+        for moves in ["3,2,1", "3 2 1", "321", " 3-2.\t1\n", "3, 2, 1"] {
+            let mut game = Game::new();
+            game.play_str_lenient(moves)?;
+            assert_eq!(game, expected, "{moves}");
+        }
+
+        // The strict parser still rejects separators
+        assert_eq!(Game::new().play_str("3,2,1"), Err(MoveError::InvalidColumn));
+
+        let mut game = Game::new();
+        assert_eq!(game.play_str_lenient("3, 8"), Err(MoveError::InvalidColumn));
+        assert_eq!(game.moves(), &[2]);
+        assert_eq!(
+            Game::new().play_str_lenient("4 4 4 4 4 4 4"),
+            Err(MoveError::ColumnFull)
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn validate_str() {
+        assert_eq!(Game::validate_str(""), Ok(()));
+        assert_eq!(
+            Game::validate_str("1111111"),
+            Err((6, MoveError::ColumnFull))
+        );
+        assert_eq!(
+            Game::validate_str("1212128"),
+            Err((6, MoveError::InvalidColumn))
+        );
+        assert_eq!(
+            Game::validate_str("12121213"),
+            Err((7, MoveError::GameOver))
+        );
+    }
+
+    #[test]
+    fn column_heights() -> Result<(), MoveError> {
+        let mut game = Game::with_dimensions(9, 4);
+        game.play_slice(&[8, 8, 8, 8, 0])?;
+
+        assert_eq!(game.column_heights(), vec![1, 0, 0, 0, 0, 0, 0, 0, 4]);
+        assert!(game.is_column_full(8));
+        assert!(!game.is_column_full(0));
+        assert!(game.is_column_full(9));
+        assert!(game.is_column_full(u8::MAX));
+        Ok(())
+    }
+
+    #[test]
+    fn legal_moves() -> Result<(), MoveError> {
+        let game = Game::from_str("1111117777774444")?;
+        assert_eq!(game.legal_moves_mask(), 0b0111110);
+        assert_eq!(game.legal_moves(), vec![1, 2, 3, 4, 5]);
+
+        // Columns past the first 8 are included on wide boards
+        let mut game = Game::with_dimensions(12, 4);
+        game.play_slice(&[11, 11, 11, 11, 0, 0, 0, 0])?;
+        assert_eq!(game.legal_moves_mask(), 0b0111_1111_1110);
+        assert_eq!(game.legal_moves(), (1..11).collect::<Vec<_>>());
+
+        for col in 0..game.width() {
+            assert_eq!(
+                game.legal_moves_mask() & 1 << col != 0,
+                game.can_play(col).is_ok()
+            );
+        }
+
+        let game = Game::from_str("112233")?;
+        assert_eq!(game.legal_moves().len(), 7);
+        let game = Game::from_str("1122334")?;
+        assert_eq!(game.legal_moves_mask(), 0);
+        assert!(game.legal_moves().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn outcome() -> Result<(), MoveError> {
+        for moves in [
+            "",
+            "4455",
+            "4455667",
+            "111111222222333333544444455555666666777777",
+        ] {
+            let game = Game::from_str(moves)?;
+            assert_eq!(game.outcome(), game.status().into());
+            assert_eq!(
+                game.outcome().map(Status::from).unwrap_or(Status::Ongoing),
+                game.status()
+            );
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn dead_draw() -> Result<(), MoveError> {
+        // _ O _ X _ O _
+        // _ X _ O _ X _
+        // O O O X O O O
+        // X X X O X X X
+        // O O O X O O O
+        // X X X O X X X
+        let mut grid = Game::from_str("111111222222333333544444455555666666777777")?.matrix();
+        for row in &mut grid[4..] {
+            for col in [0, 2, 4, 6] {
+                row[col] = None;
+            }
+        }
+
+        let game = Game::from_matrix(grid)?;
+        assert!(game.is_dead_draw());
+        assert_eq!(game.status(), Status::Ongoing);
+
+        assert!(!Game::new().is_dead_draw());
+        assert!(!Game::from_str("4455667")?.is_dead_draw());
+        Ok(())
+    }
+
+    #[test]
+    fn move_history() -> Result<(), MoveError> {
+        let mut game = Game::from_matrix(Game::from_str("4444")?.matrix())?;
+        game.play_slice(&[3, 0])?;
+
+        assert_eq!(
+            game.move_history(),
+            vec![
+                MoveRecord {
+                    player: Player::P1,
+                    col: 3,
+                    row: 4
+                },
+                MoveRecord {
+                    player: Player::P2,
+                    col: 0,
+                    row: 0
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn no_win_overflow() -> Result<(), MoveError> {
+        // _ X _ _ _ _ _
+        // _ X _ _ _ _ _
+        // _ X _ _ _ _ _
+        // X O _ _ _ _ _
+        // X O O _ _ _ _
+        // X O O _ _ _ _
+        let game = Game::from_str("12121223232")?;
+
+        assert!(!game.is_over());
+        assert_eq!(game.status(), Status::Ongoing);
+        assert_eq!(game.win_coords(), None);
+        Ok(())
+    }
+
+    fn test_end_game(
+        moves: &str,
+        status: Status,
+        win_coords: Option<[(u8, u8); 4]>,
+    ) -> Result<(), MoveError> {
+        let (first, last) = moves.split_at(moves.len() - 1);
+
+        let mut game = Game::from_str(first)?;
+        assert!(!game.is_over());
+        assert_eq!(game.status(), Status::Ongoing);
+
+        game.play_str(last)?;
+        assert!(game.is_over());
+        assert_eq!(game.status(), status);
+        assert_eq!(game.win_coords(), win_coords);
+        assert_eq!(game.play(0), Err(MoveError::GameOver));
+        Ok(())
+    }
+
+    #[test]
+    fn horizontal_win() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // O O O _ _ _ _
+        // X X X X _ _ _
+        test_end_game(
+            "1122334",
+            Status::Win(Player::P1),
+            Some([(0, 0), (1, 0), (2, 0), (3, 0)]),
+        )
+    }
+
+    #[test]
+    fn vertical_win() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // X _ _ _ _ _ _
+        // X O _ _ _ _ _
+        // X O _ _ _ _ _
+        // X O _ _ _ _ _
+        test_end_game(
+            "1212121",
+            Status::Win(Player::P1),
+            Some([(0, 0), (0, 1), (0, 2), (0, 3)]),
+        )
+    }
+
+    #[test]
+    fn ascending_diagonal_win() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // _ _ _ O _ _ _
+        // _ _ O X _ _ _
+        // _ O X O _ _ _
+        // O X X X _ _ _
+        test_end_game(
+            "4122343344",
+            Status::Win(Player::P2),
+            Some([(0, 0), (1, 1), (2, 2), (3, 3)]),
+        )
+    }
+
+    #[test]
+    fn descending_diagonal_win() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // _ _ _ O _ _ _
+        // _ _ _ X O _ _
+        // _ _ _ O X O _
+        // _ _ _ X X X O
+        test_end_game(
+            "4766545544",
+            Status::Win(Player::P2),
+            Some([(3, 3), (4, 2), (5, 1), (6, 0)]),
+        )
+    }
+
+    #[test]
+    fn descending_diagonal_win_from_edge() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _
+        // X _ _ _ _ _ _
+        // O X _ _ _ _ _
+        // X O X _ _ _ _
+        // O X O X _ _ O
+        test_end_game(
+            "41112223371",
+            Status::Win(Player::P1),
+            Some([(0, 3), (1, 2), (2, 1), (3, 0)]),
+        )?;
+
+        let game = Game::from_str("41112223371")?;
+        assert_eq!(
+            game.all_win_coords(),
+            vec![vec![(0, 3), (1, 2), (2, 1), (3, 0)]]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn win_line_direction() -> Result<(), MoveError> {
+        for (moves, direction, player) in [
+            ("1122334", Direction::Horizontal, Player::P1),
+            ("1212121", Direction::Vertical, Player::P1),
+            ("4122343344", Direction::AscendingDiagonal, Player::P2),
+            ("4766545544", Direction::DescendingDiagonal, Player::P2),
+            ("41112223371", Direction::DescendingDiagonal, Player::P1),
+        ] {
+            let game = Game::from_str(moves)?;
+            let line = game.win_line().unwrap();
+            assert_eq!(line.direction, direction, "{moves}");
+            assert_eq!(line.player, player, "{moves}");
+            assert_eq!(Some(line.coords), game.win_line_coords(), "{moves}");
+        }
+        assert_eq!(Game::from_str("4453")?.win_line(), None);
+        Ok(())
+    }
 
-        nodes
+    #[test]
+    fn multiple_wins() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // O O _ _ _ _ O
+        // X X X X X X X
+        // O O X X X O O
+        // O X O X O X O
+        // X O O X O O X
+        test_end_game(
+            "1226716747711226634543355137524",
+            Status::Win(Player::P1),
+            // currently chooses ascending diagonal over other directions, but the implementation is subject to change in the future
+            Some([(0, 0), (1, 1), (2, 2), (3, 3)]),
+        )
     }
-}
 
-impl FromStr for Game {
-    type Err = MoveError;
+    #[test]
+    fn multiple_win_lines() -> Result<(), MoveError> {
+        let game = Game::from_str("1226716747711226634543355137524")?;
+        let lines = game.all_win_coords();
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::from_str(s)
+        assert!(lines.len() > 1);
+        assert!(lines.contains(&game.win_line_coords().unwrap()));
+        assert!(lines.contains(&vec![(0, 3), (1, 3), (2, 3), (3, 3)]));
+        assert!(lines.contains(&vec![(3, 3), (4, 3), (5, 3), (6, 3)]));
+
+        let mut deduped = lines.clone();
+        deduped.dedup();
+        assert_eq!(deduped, lines);
+
+        assert_eq!(Game::from_str("1122334")?.all_win_coords().len(), 1);
+        assert!(Game::from_str("112233")?.all_win_coords().is_empty());
+        Ok(())
     }
-}
 
-impl fmt::Display for Game {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let mut matrix = self.matrix();
-        matrix.reverse();
+    #[test]
+    fn winning_move_coord() -> Result<(), MoveError> {
+        let game = Game::from_str("1226716747711226634543355137524")?;
+        assert_eq!(game.winning_move_coord(), Some((3, 3)));
+
+        let game = Game::from_str("111112222233333144444255555376666667777754")?;
+        assert_eq!(game.winning_move_coord(), Some((3, 5)));
+
+        assert_eq!(Game::from_str("112233")?.winning_move_coord(), None);
+        assert_eq!(Game::new().winning_move_coord(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn last_win() -> Result<(), MoveError> {
+        // O O O O X X O
+        // X O X X X O X
+        // O X O O O X O
+        // X O X X X O X
+        // O X O O O X O
+        // X O X X X O X
+        test_end_game(
+            "111112222233333144444255555376666667777754",
+            Status::Win(Player::P2),
+            Some([(0, 5), (1, 5), (2, 5), (3, 5)]),
+        )
+    }
+
+    #[test]
+    fn draw() -> Result<(), MoveError> {
+        // O O O X O O O
+        // X X X O X X X
+        // O O O X O O O
+        // X X X O X X X
+        // O O O X O O O
+        // X X X O X X X
+        test_end_game(
+            "111111222222333333544444455555666666777777",
+            Status::Draw,
+            None,
+        )
+    }
+
+    #[test]
+    fn format_string() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ X
+        // _ _ _ _ _ X O
+        // _ _ _ _ O O X
+        // _ _ _ O X X O
+        // _ _ X X O O X
+        // _ X O O X X O
+        let game = Game::from_str("233444555566666777777")?;
+
+        assert_eq!(game.to_string(), "_ _ _ _ _ _ X\n_ _ _ _ _ X O\n_ _ _ _ O O X\n_ _ _ O X X O\n_ _ X X O O X\n_ X O O X X O");
+
+        assert!(format!("{game:b}").starts_with("P1\n0 0 0 0 0 0 1\n"));
+        assert!(format!("{game:#b}").starts_with("P1\nS | 0 0 0 0 0 0 0\n5 | 0 0 0 0 0 0 1\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn format_labels() -> Result<(), MoveError> {
+        let mut game = Game::with_dimensions(10, 4);
+        game.play_slice(&[9, 0, 9])?;
+
+        assert_eq!(
+            game.display_with_labels(),
+            " 1  2  3  4  5  6  7  8  9 10\n _  _  _  _  _  _  _  _  _  _\n _  _  _  _  _  _  _  _  _  _\n _  _  _  _  _  _  _  _  _  X\n O  _  _  _  _  _  _  _  _  X"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unicode_string() -> Result<(), MoveError> {
+        let expected = "\
+│ · · · · · · · │
+│ · · · · · · · │
+│ · · · · · · · │
+│ · · · · · · · │
+│ · · · · · · · │
+│ · · · · · · · │
+└───────────────┘
+  1 2 3 4 5 6 7";
+        assert_eq!(Game::new().to_unicode(), expected);
+
+        let mut game = Game::with_dimensions(10, 4);
+        game.play_slice(&[9, 0])?;
+        let expected = "\
+│  ·  ·  ·  ·  ·  ·  ·  ·  ·  · │
+│  ·  ·  ·  ·  ·  ·  ·  ·  ·  · │
+│  ·  ·  ·  ·  ·  ·  ·  ·  ·  · │
+│  ○  ·  ·  ·  ·  ·  ·  ·  ·  ● │
+└───────────────────────────────┘
+   1  2  3  4  5  6  7  8  9 10";
+        assert_eq!(game.to_unicode(), expected);
+        Ok(())
+    }
 
-        fn fmt_tile(tile: Option<Player>) -> String {
-            tile.map_or("_".into(), |player| player.to_string())
+    #[test]
+    fn packed_round_trip() -> Result<(), MoveError> {
+        for moves in [
+            "",
+            "4",
+            "233444555566666777777",
+            "274552224131661",
+            "444444",
+        ] {
+            let game = Game::from_str(moves)?;
+            let bytes = game.to_packed();
+            assert_eq!(bytes.len(), 1 + (moves.len() * 3).div_ceil(8));
+            assert_eq!(Game::from_packed(&bytes)?.moves(), game.moves());
         }
 
-        let rows = matrix.map(|row| row.map(fmt_tile).join(" "));
+        let draw = Game::from_str("111111222222333333544444455555666666777777")?;
+        assert_eq!(draw.to_packed().len(), 17);
+        assert_eq!(Game::from_packed(&draw.to_packed())?.moves(), draw.moves());
+        Ok(())
+    }
 
-        write!(f, "{}", rows.join("\n"))
+    #[test]
+    fn packed_invalid() {
+        assert_eq!(Game::from_packed(&[]), Err(MoveError::InvalidFormat));
+        assert_eq!(Game::from_packed(&[1]), Err(MoveError::InvalidFormat));
+        assert_eq!(Game::from_packed(&[0, 0]), Err(MoveError::InvalidFormat));
+        assert_eq!(
+            Game::from_packed(&[2, 0b1000_0000]),
+            Err(MoveError::InvalidFormat)
+        );
+        assert_eq!(Game::from_packed(&[7, 0, 0, 0]), Err(MoveError::ColumnFull));
     }
-}
 
-impl fmt::Binary for Game {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let bitboards = if self.turn() == Player::P1 {
-            (self.board.player_bb(), self.board.opponent_bb())
-        } else {
-            (self.board.opponent_bb(), self.board.player_bb())
-        };
+    #[test]
+    #[cfg(feature = "rand")]
+    fn play_random() -> Result<(), MoveError> {
+        use rand::{rngs::SmallRng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let mut game = Game::from_str("111111")?;
+            let mut moves = game.moves().to_vec();
+
+            while let Some(col) = game.play_random(&mut rng) {
+                assert_ne!(col, 0);
+                moves.push(col);
+            }
+            assert!(game.is_over());
+            assert_eq!(game.moves(), moves);
+        }
+        Ok(())
+    }
 
-        writeln!(f, "{:?}", Player::P1)?;
-        writeln!(f, "{}", bitboard::format(bitboards.0))?;
-        writeln!(f)?;
-        writeln!(f, "{:?}", Player::P2)?;
-        write!(f, "{}", bitboard::format(bitboards.1))
+    #[test]
+    #[cfg(feature = "std")]
+    fn play_and_check() {
+        use rand::{rngs::SmallRng, Rng, SeedableRng};
+
+        let mut rng = SmallRng::seed_from_u64(0);
+        for (width, height, connect) in [(7, 6, 4), (8, 7, 4), (12, 4, 4), (9, 6, 5), (6, 5, 5)] {
+            for _ in 0..500 {
+                let mut board = Board::with_connect_length(width, height, connect);
+                while !board.is_terminal() {
+                    let possible = board.possible_bb();
+                    let cols: Vec<u8> = (0..width).filter(|&col| board.is_open(col)).collect();
+                    let col = cols[rng.gen_range(0..cols.len())];
+
+                    let won = board.play_and_check(possible & bitboard::column_mask(col, height));
+                    assert_eq!(
+                        won,
+                        board.has_opponent_won(),
+                        "{width}x{height} connect {connect}"
+                    );
+                }
+            }
+        }
     }
-}
 
-#[cfg(test)]
-#[allow(clippy::unusual_byte_groupings)]
-mod tests {
-    use super::*;
-    use std::fs::File;
-    use std::io::{prelude::*, BufReader};
-    use std::ops::RangeBounds;
+    #[test]
+    fn last_move() -> Result<(), MoveError> {
+        let mut game = Game::from_str("4444")?;
+        assert_eq!(game.last_move(), Some(3));
+        assert_eq!(game.last_move_coord(), Some((3, 3)));
+
+        game.undo();
+        assert_eq!(game.last_move_coord(), Some((3, 2)));
+
+        // Games without a move history have no last move
+        let game = Game::from_matrix(game.matrix())?;
+        assert_eq!(game.last_move(), None);
+        assert_eq!(game.last_move_coord(), None);
+        Ok(())
+    }
 
     #[test]
-    fn new_game() {
-        let game = Game::new();
-        assert_eq!(game.board.player_bb(), 0);
-        assert_eq!(game.board.occupied_bb(), 0);
+    fn undo_moves() -> Result<(), MoveError> {
+        let init_game = Game::new();
+        let mut game = Game::from_str("111112222233333144444255555376666667777754")?;
+
+        for i in 0..game.num_moves() {
+            assert_eq!(game.num_moves(), AREA - i);
+            game.undo();
+        }
+
+        assert_eq!(init_game, game);
         assert_eq!(game.num_moves(), 0);
+        assert_eq!(game.moves().len(), 0);
+        assert_eq!(game.undo(), None);
+        Ok(())
+    }
+
+    #[test]
+    fn undo_many_moves() -> Result<(), MoveError> {
+        let mut game = Game::from_str("111112222233333144444255555376666667777754")?;
+
+        assert_eq!(game.undo_n(0), 0);
+        assert_eq!(game.undo_n(30), 30);
+        assert_eq!(game, Game::from_str("111112222233")?);
+
+        assert_eq!(game.undo_n(usize::MAX), 12);
+        assert_eq!(game, Game::new());
+        assert_eq!(game.undo_n(1), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn truncate_to() -> Result<(), MoveError> {
+        let moves = "111112222233333144444255555376666667777754";
+        let mut game = Game::from_str(moves)?;
+
+        game.truncate_to(10)?;
+        assert_eq!(game, Game::from_str(&moves[..10])?);
+        assert_eq!(
+            game.position_key(),
+            Game::from_str(&moves[..10])?.position_key()
+        );
+
+        game.truncate_to(8)?;
+        assert_eq!(game, Game::from_str(&moves[..8])?);
+        game.truncate_to(8)?;
+        assert_eq!(game.num_moves(), 8);
+        assert_eq!(game.truncate_to(9), Err(MoveError::InvalidPly));
+
+        game.truncate_to(0)?;
+        assert_eq!(game, Game::new());
+
+        // Moves without a recorded history cannot be undone
+        let mut game = Game::from_matrix(Game::from_str("4453")?.matrix())?;
+        game.play(0)?;
+        game.truncate_to(4)?;
+        assert_eq!(game.truncate_to(2), Err(MoveError::InvalidPly));
+        Ok(())
+    }
+
+    #[test]
+    fn pop_out() -> Result<(), MoveError> {
+        // Popping out completes lines for both players, and the player who popped out wins
+        // _ _ _ X _ _ _
+        // X X X O _ _ _
+        // O O O X _ _ O
+        let original = Game::from_cells(&format!("222100211120000001000{}", "0".repeat(21)))?;
+        let mut game = original.clone();
+        game.pop_out(3)?;
+        assert_eq!(game.column_heights(), vec![2, 2, 2, 2, 0, 0, 1]);
+        assert_eq!(game.status(), Status::Win(Player::P1));
+        assert_eq!(
+            game.all_win_coords(),
+            vec![vec![(0, 1), (1, 1), (2, 1), (3, 1)]]
+        );
+        assert_eq!(game.pop_out(0), Err(MoveError::GameOver));
+        assert_eq!(game.play(4), Err(MoveError::GameOver));
+
+        assert_eq!(game.undo(), Some(3));
+        assert_eq!(game, original);
         assert!(!game.is_over());
+
+        // Popping out can complete a line for the opponent, who is then the player to move
+        // X X X O _ _ _
+        // O O O X _ _ _
+        let mut game = Game::from_cells(&format!("22210001112000{}", "0".repeat(28)))?;
+        game.pop_out(3)?;
+        assert_eq!(game.turn(), Player::P2);
+        assert_eq!(game.status(), Status::Win(Player::P2));
+        assert_eq!(
+            game.win_line().map(|line| (line.coords, line.player)),
+            Some((vec![(0, 0), (1, 0), (2, 0), (3, 0)], Player::P2))
+        );
+
+        // Moves can continue after a pop out
+        let mut game = Game::from_str("4455")?;
+        game.pop_out(3)?;
+        game.play_slice(&[3, 3])?;
+        assert_eq!(game.moves(), &[3, 3]);
+        assert_eq!(game.num_moves(), 7);
         assert_eq!(game.status(), Status::Ongoing);
+        assert_eq!(game.at(3, 2), Some(Player::P1));
+
+        // Undoing the pop out restores the move history
+        assert_eq!(game.undo_n(3), 3);
+        assert_eq!(game, Game::from_str("4455")?);
+        Ok(())
     }
 
     #[test]
-    fn play_one() -> Result<(), MoveError> {
+    fn pop_out_move_limit() -> Result<(), MoveError> {
+        // Playing and popping out the same pieces never fills the board
         let mut game = Game::new();
-        game.play(3)?;
+        let error = loop {
+            game.play_slice(&[0, 1])?;
+            if let Err(error) = game.pop_out(0) {
+                break error;
+            }
+            game.pop_out(1)?;
+        };
+
+        assert_eq!(error, MoveError::TooManyMoves);
+        assert_eq!(game.num_moves(), 214);
+        assert_eq!(game.moves(), &[0, 1]);
+        Ok(())
+    }
+
+    #[test]
+    fn pop_out_illegal() -> Result<(), MoveError> {
+        let mut game = Game::from_str("4453")?;
+        let copy = game.clone();
+
+        assert_eq!(game.pop_out(2), Err(MoveError::NotOwnPiece));
+        assert_eq!(game.pop_out(0), Err(MoveError::NotOwnPiece));
+        assert_eq!(game.pop_out(7), Err(MoveError::InvalidColumn));
+        assert_eq!(game, copy);
+
+        // Pieces above the bottom of the column cannot be popped out
+        game.play(2)?;
+        assert_eq!(game.pop_out(3), Err(MoveError::NotOwnPiece));
+        Ok(())
+    }
+
+    #[test]
+    fn variant_bounds() -> Result<(), MoveError> {
+        let mut game = Game::with_dimensions(6, 5);
+        game.play_slice(&[5, 5, 5, 5, 5])?;
+
+        assert_eq!(game.play(5), Err(MoveError::ColumnFull));
+        assert_eq!(game.play(6), Err(MoveError::InvalidColumn));
+        assert_eq!(game.grid().len(), 5);
+        assert!(game.grid().iter().all(|row| row.len() == 6));
+        Ok(())
+    }
+
+    #[test]
+    fn variant_wins() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _ _
+        // _ _ _ _ _ _ _ _
+        // _ _ _ _ O O O _
+        // _ _ _ _ X X X X
+        let mut game = Game::with_dimensions(8, 7);
+        game.play_slice(&[4, 4, 5, 5, 6, 6, 7])?;
+        assert_eq!(game.status(), Status::Win(Player::P1));
+        assert_eq!(game.win_coords(), Some([(4, 0), (5, 0), (6, 0), (7, 0)]));
+
+        // _ _ _ _ _ _
+        // _ _ _ O _ _
+        // _ _ O X _ _
+        // _ O X O _ _
+        // O X X X _ _
+        let mut game = Game::with_dimensions(6, 5);
+        game.play_str("4122343344")?;
+        assert_eq!(game.status(), Status::Win(Player::P2));
+        assert_eq!(game.win_coords(), Some([(0, 0), (1, 1), (2, 2), (3, 3)]));
+
+        // _ X _ _ _ _ _ _
+        // _ X _ _ _ _ _ _
+        // _ X _ _ _ _ _ _
+        // X O _ _ _ _ _ _
+        // X O O _ _ _ _ _
+        // X O O _ _ _ _ _
+        let mut game = Game::with_dimensions(8, 6);
+        game.play_str("12121223232")?;
+        assert_eq!(game.status(), Status::Ongoing);
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic]
+    fn variant_too_large() {
+        let _ = Game::with_dimensions(8, 8);
+    }
+
+    #[test]
+    fn feature_planes() -> Result<(), MoveError> {
+        let planes = Game::new().feature_planes();
+        assert!(planes[..2].iter().flatten().flatten().all(|&x| x == 0.0));
+        assert!(planes[2].iter().flatten().all(|&x| x == 1.0));
+
+        // _ _ _ _ _ _ _
+        // _ _ _ O _ _ _
+        // _ _ O X X _ _
+        let game = Game::from_str("4453")?;
+        let planes = game.feature_planes();
+        let matrix = game.matrix();
+        for (y, row) in matrix.iter().enumerate() {
+            for (x, &tile) in row.iter().enumerate() {
+                assert_eq!(planes[0][y][x] == 1.0, tile == Some(Player::P1));
+                assert_eq!(planes[1][y][x] == 1.0, tile == Some(Player::P2));
+            }
+        }
+        assert_eq!(planes[0][0][3], 1.0);
+        assert_eq!(planes[1][1][3], 1.0);
+
+        // The planes of the players swap after each move
+        let planes = Game::from_str("44532")?.feature_planes();
+        assert_eq!(planes[0][1][3], 1.0);
+        assert_eq!(planes[1][0][1], 1.0);
+        assert!(planes[2].iter().flatten().all(|&x| x == 0.0));
+        Ok(())
+    }
+
+    #[test]
+    fn from_matrix() -> Result<(), MoveError> {
+        for moves in ["", "4", "233444555566666777777", "4122343344"] {
+            let game = Game::from_str(moves)?;
+            let copy = Game::from_matrix(game.matrix())?;
+
+            assert_eq!(copy.board, game.board);
+            assert_eq!(copy.status(), game.status());
+            assert_eq!(copy.moves(), &[]);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn from_bitboards() -> Result<(), MoveError> {
+        for moves in ["", "4", "233444555566666777777", "4122343344"] {
+            let game = Game::from_str(moves)?;
+            let copy = Game::from_bitboards(game.board.player_bb(), game.board.occupied_bb())?;
+
+            assert_eq!(copy.board, game.board);
+            assert_eq!(copy.moves(), &[]);
+        }
+
+        // Pieces outside the board, or pieces of the player that are not occupied
         assert_eq!(
-            game.board.player_bb(),
-            0b_0000000_0000000_0000000_0000000_0000000_0000000_0000000
+            Game::from_bitboards(0, 1 << 13),
+            Err(MoveError::InvalidFormat)
+        );
+        assert_eq!(
+            Game::from_bitboards(0, 1 << 49),
+            Err(MoveError::InvalidFormat)
+        );
+        assert_eq!(Game::from_bitboards(1, 0), Err(MoveError::InvalidFormat));
+
+        assert_eq!(
+            Game::from_bitboards(0, 1 << 7 | 1 << 9),
+            Err(MoveError::FloatingPiece)
+        );
+        assert_eq!(
+            Game::from_bitboards(0b11, 0b11),
+            Err(MoveError::UnbalancedPieces)
+        );
+        assert_eq!(Game::from_bitboards(1, 1), Err(MoveError::UnbalancedPieces));
+
+        // The player to move already has four in a row in the first column
+        assert_eq!(
+            Game::from_bitboards(0b1111, 0b1111 | 1 << 7 | 1 << 14 | 1 << 21 | 1 << 28),
+            Err(MoveError::PlayedAfterWin)
         );
+        Ok(())
+    }
+
+    #[test]
+    fn from_matrix_unreachable() -> Result<(), MoveError> {
+        let mut grid = [[None; WIDTH as usize]; HEIGHT as usize];
+        grid[0][0] = Some(Player::P2);
+        assert_eq!(Game::from_matrix(grid), Err(MoveError::UnbalancedPieces));
+
+        grid[0][1] = Some(Player::P1);
+        grid[0][2] = Some(Player::P1);
+        grid[0][3] = Some(Player::P1);
+        assert_eq!(Game::from_matrix(grid), Err(MoveError::UnbalancedPieces));
+
+        grid[2][3] = Some(Player::P2);
+        assert_eq!(Game::from_matrix(grid), Err(MoveError::FloatingPiece));
+
+        // X X X X _ _ _
+        // O O O O _ _ _
+        let mut grid = [[None; WIDTH as usize]; HEIGHT as usize];
+        grid[0][..4].fill(Some(Player::P2));
+        grid[1][..4].fill(Some(Player::P1));
+        assert_eq!(Game::from_matrix(grid), Err(MoveError::PlayedAfterWin));
+        Ok(())
+    }
+
+    #[test]
+    fn double_threat() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ O O O _ _
+        // _ _ X X X _ _
+        let mut game = Game::from_str("334455")?;
+        assert_eq!(game.threats(), (vec![1, 5], vec![]));
+
+        game.play(0)?;
+        assert_eq!(game.threats(), (vec![], vec![1, 5]));
+
+        game.play(5)?;
+        assert_eq!(game.threats(), (vec![1], vec![5]));
+
+        game.play(1)?;
+        assert_eq!(game.threats(), (vec![], vec![]));
+        Ok(())
+    }
+
+    #[test]
+    fn creates_fork() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ O O _ _ _
+        // _ _ X X _ _ _
+        let game = Game::from_str("3344")?;
+        let forks: Vec<u8> = (0..WIDTH)
+            .filter(|&col| game.creates_fork(col) == Ok(true))
+            .collect();
+        assert_eq!(forks, vec![1, 4]);
+
+        // The fork is a double threat for the player who made it
+        let mut forked = game.clone();
+        forked.play(4)?;
         assert_eq!(
-            game.board.occupied_bb(),
-            0b_0000000_0000000_0000000_0000001_0000000_0000000_0000000
+            forked.detect_motifs(),
+            vec![Motif::DoubleThreat(Player::P1)]
         );
-        assert_eq!(game.num_moves(), 1);
+
+        // Winning immediately is not a fork
+        let game = Game::from_str("334455")?;
+        assert_eq!(game.creates_fork(1), Ok(false));
+
+        let game = Game::from_str("111111")?;
+        assert_eq!(game.creates_fork(0), Err(MoveError::ColumnFull));
         Ok(())
     }
 
     #[test]
-    fn play_multiple() -> Result<(), MoveError> {
-        let mut game1 = Game::new();
-        game1.play_slice(&[3, 3, 3, 3])?;
+    fn detect_motifs() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
+        // _ _ O O O _ _
+        // _ _ X X X _ _
+        let mut game = Game::from_str("334455")?;
+        assert_eq!(game.detect_motifs(), vec![Motif::DoubleThreat(Player::P1)]);
 
-        let mut game2 = Game::new();
-        game2.play_str("4444")?;
+        game.play(0)?;
+        assert_eq!(game.detect_motifs(), vec![Motif::DoubleThreat(Player::P1)]);
+
+        // Blocking one side leaves a single threat for each player
+        game.play(5)?;
+        assert!(game.detect_motifs().is_empty());
 
         assert_eq!(
-            game1.board.player_bb(),
-            0b_0000000_0000000_0000000_0000101_0000000_0000000_0000000
-        );
-        assert_eq!(
-            game1.board.occupied_bb(),
-            0b_0000000_0000000_0000000_0001111_0000000_0000000_0000000
+            Game::from_str("1744543537")?.detect_motifs(),
+            vec![Motif::DoubleThreat(Player::P1)]
         );
-        assert_eq!(game1.num_moves(), 4);
-        assert_eq!(game1, game2);
+
+        game.play(1)?;
+        assert!(game.detect_motifs().is_empty());
         Ok(())
     }
 
     #[test]
-    fn out_of_bounds() {
-        let mut game = Game::new();
-        let result = game.play(7);
-        assert_eq!(result, Err(MoveError::InvalidColumn));
-    }
+    fn is_quiet() -> Result<(), MoveError> {
+        assert!(Game::new().is_quiet());
+        assert!(Game::from_str("4453")?.is_quiet());
 
-    #[test]
-    fn full_column() {
-        let mut game = Game::new();
-        let result = game.play_str("1111111");
-        assert_eq!(result, Err(MoveError::ColumnFull));
+        // The threats in the double threat position are not quiet for either player to move
+        let mut game = Game::from_str("334455")?;
+        assert!(!game.is_quiet());
+        assert!(!Game::from_str("3344551")?.is_quiet());
+
+        // The game has ended with the first player's threat
+        game.play(1)?;
+        assert!(game.is_over());
+        assert!(game.is_quiet());
+        Ok(())
     }
 
     #[test]
-    fn no_win_overflow() -> Result<(), MoveError> {
-        // _ X _ _ _ _ _
-        // _ X _ _ _ _ _
-        // _ X _ _ _ _ _
-        // X O _ _ _ _ _
-        // X O O _ _ _ _
-        // X O O _ _ _ _
-        let game = Game::from_str("12121223232")?;
+    fn replay() -> Result<(), MoveError> {
+        let game = Game::from_str("111112222233333144444255555376666667777754")?;
 
-        assert!(!game.is_over());
-        assert_eq!(game.status(), Status::Ongoing);
-        assert_eq!(game.win_coords(), None);
+        for (i, state) in game.replay().enumerate() {
+            let mut expected = game.clone();
+            while usize::from(expected.num_moves()) > i + 1 {
+                expected.undo();
+            }
+
+            assert_eq!(state, expected);
+            assert_eq!(state.matrix(), expected.matrix());
+        }
+        assert_eq!(game.replay().last(), Some(game.clone()));
+
+        let mut game = Game::with_dimensions(8, 7);
+        game.play_slice(&[7, 7, 0])?;
+        assert_eq!(game.replay().last(), Some(game));
+
+        let loaded = Game::from_matrix(Game::from_str("5554224333234511764415115")?.matrix())?;
+        let mut game = loaded.clone();
+        game.play_slice(&[0, 5])?;
+        let states: Vec<Game> = game.replay().collect();
+        assert_eq!(states.len(), 2);
+        assert_eq!(states[0].num_moves(), loaded.num_moves() + 1);
+        assert_eq!(states[1], game);
+        assert_eq!(loaded.replay().count(), 0);
+
+        let mut game = Game::from_str("4455")?;
+        game.pop_out(3)?;
+        game.play_slice(&[0, 0, 0])?;
+        let states: Vec<Game> = game.replay().collect();
+        assert_eq!(states.len(), 3);
+        assert_eq!(states[2], game);
         Ok(())
     }
 
-    fn test_end_game(
-        moves: &str,
-        status: Status,
-        win_coords: Option<[(u8, u8); 4]>,
-    ) -> Result<(), MoveError> {
-        let (first, last) = moves.split_at(moves.len() - 1);
+    #[test]
+    fn children() -> Result<(), MoveError> {
+        let children = Game::new().children();
+        assert_eq!(children.len(), 7);
+        assert!(children
+            .iter()
+            .all(|(col, child)| child.moves() == [*col] && child.num_moves() == 1));
+
+        assert!(Game::from_str("1212121")?.children().is_empty());
+        Ok(())
+    }
 
-        let mut game = Game::from_str(first)?;
-        assert!(!game.is_over());
-        assert_eq!(game.status(), Status::Ongoing);
+    #[test]
+    fn from_cells() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ X
+        // _ _ _ _ _ X O
+        // _ _ _ _ O O X
+        // _ _ _ O X X O
+        // _ _ X X O O X
+        // _ X O O X X O
+        let game = Game::from_cells("012211200112210002112000022100000120000001")?;
+        assert_eq!(game.board, Game::from_str("233444555566666777777")?.board);
 
-        game.play_str(last)?;
-        assert!(game.is_over());
-        assert_eq!(game.status(), status);
-        assert_eq!(game.win_coords(), win_coords);
-        assert_eq!(game.play(0), Err(MoveError::GameOver));
+        // O O O X O O O
+        // X X X O X X X
+        // O O O X O O O
+        // X X X O X X X
+        // O O O X O O O
+        // X X X O X X X
+        let game = Game::from_cells("111211122212221112111222122211121112221222")?;
+        assert_eq!(
+            game.board,
+            Game::from_str("111111222222333333544444455555666666777777")?.board
+        );
+        assert_eq!(game.status(), Status::Draw);
         Ok(())
     }
 
     #[test]
-    fn horizontal_win() -> Result<(), MoveError> {
-        // _ _ _ _ _ _ _
-        // _ _ _ _ _ _ _
-        // _ _ _ _ _ _ _
-        // _ _ _ _ _ _ _
-        // O O O _ _ _ _
-        // X X X X _ _ _
-        test_end_game(
-            "1122334",
-            Status::Win(Player::P1),
-            Some([(0, 0), (1, 0), (2, 0), (3, 0)]),
-        )
+    fn from_cells_invalid() {
+        assert_eq!(Game::from_cells(""), Err(MoveError::InvalidFormat));
+        assert_eq!(
+            Game::from_cells(&"3".repeat(AREA.into())),
+            Err(MoveError::InvalidFormat)
+        );
+        assert_eq!(
+            Game::from_cells(&format!("{}1", "0".repeat(usize::from(AREA) - 1))),
+            Err(MoveError::FloatingPiece)
+        );
+        assert_eq!(
+            Game::from_cells(&format!("11{}", "0".repeat(usize::from(AREA) - 2))),
+            Err(MoveError::UnbalancedPieces)
+        );
     }
 
     #[test]
-    fn vertical_win() -> Result<(), MoveError> {
-        // _ _ _ _ _ _ _
-        // _ _ _ _ _ _ _
-        // X _ _ _ _ _ _
-        // X O _ _ _ _ _
-        // X O _ _ _ _ _
-        // X O _ _ _ _ _
-        test_end_game(
-            "1212121",
-            Status::Win(Player::P1),
-            Some([(0, 0), (0, 1), (0, 2), (0, 3)]),
-        )
+    fn opening_name() -> Result<(), MoveError> {
+        assert_eq!(Game::from_str("1")?.opening_name(), Some("Edge opening"));
+        assert_eq!(Game::from_str("6")?.opening_name(), Some("Flank opening"));
+        assert_eq!(
+            Game::from_str("4711")?.opening_name(),
+            Some("Center opening, edge reply")
+        );
+        assert_eq!(Game::from_str("4445")?.opening_name(), Some("Center tower"));
+
+        let game = Game::from_str("444")?;
+        assert_eq!(Game::from_matrix(game.matrix())?.opening_name(), None);
+        assert_eq!(Game::with_dimensions(8, 7).opening_name(), None);
+
+        let mut game = Game::from_matrix(Game::from_str("1122")?.matrix())?;
+        game.play_slice(&[3, 3])?;
+        assert_eq!(game.opening_name(), None);
+
+        let mut game = Game::from_str("4455")?;
+        game.pop_out(3)?;
+        game.play_slice(&[0, 0, 0])?;
+        assert_eq!(game.opening_name(), None);
+
+        let mut game = Game::with_connect_length(WIDTH, HEIGHT, 5);
+        game.play(3)?;
+        assert_eq!(game.opening_name(), None);
+        Ok(())
     }
 
     #[test]
-    fn ascending_diagonal_win() -> Result<(), MoveError> {
+    fn threat_parity() -> Result<(), MoveError> {
+        // _ _ _ _ _ _ _
         // _ _ _ _ _ _ _
         // _ _ _ _ _ _ _
         // _ _ _ O _ _ _
-        // _ _ O X _ _ _
-        // _ O X O _ _ _
-        // O X X X _ _ _
-        test_end_game(
-            "4122343344",
-            Status::Win(Player::P2),
-            Some([(0, 0), (1, 1), (2, 2), (3, 3)]),
-        )
+        // _ _ X O O _ O
+        // X _ X X X _ O
+        let game = Game::from_str("1744543537")?;
+        assert_eq!(
+            game.threat_parity(),
+            ThreatParity {
+                p1_odd: 2,
+                p1_even: 0,
+                p2_odd: 0,
+                p2_even: 1
+            }
+        );
+
+        // The second player's threat in the 6th column is counted even though it is not playable yet
+        assert_eq!(game.threats(), (vec![1, 5], vec![]));
+        assert_eq!(Game::new().threat_parity(), ThreatParity::default());
+        assert_eq!(
+            Game::from_str("1212121")?.threat_parity(),
+            ThreatParity::default()
+        );
+        Ok(())
     }
 
     #[test]
-    fn descending_diagonal_win() -> Result<(), MoveError> {
-        // _ _ _ _ _ _ _
-        // _ _ _ _ _ _ _
-        // _ _ _ O _ _ _
-        // _ _ _ X O _ _
-        // _ _ _ O X O _
-        // _ _ _ X X X O
-        test_end_game(
-            "4766545544",
-            Status::Win(Player::P2),
-            Some([(3, 3), (4, 2), (5, 1), (6, 0)]),
-        )
+    fn connect_five() -> Result<(), MoveError> {
+        let mut game = Game::with_connect_length(9, 6, 5);
+        game.play_slice(&[0, 0, 1, 1, 2, 2, 3, 3])?;
+        assert!(!game.is_over());
+        assert_eq!(game.win_coords(), None);
+
+        game.play(4)?;
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::P1)));
+        assert_eq!(game.win_coords(), Some([(0, 0), (1, 0), (2, 0), (3, 0)]));
+        assert_eq!(
+            game.all_win_coords(),
+            vec![vec![(0, 0), (1, 0), (2, 0), (3, 0), (4, 0)]]
+        );
+
+        let mut game = Game::with_connect_length(8, 7, 5);
+        game.play_slice(&[0, 1, 0, 1, 0, 1, 0, 1])?;
+        assert!(!game.is_over());
+
+        game.play(0)?;
+        assert_eq!(game.outcome(), Some(Outcome::Win(Player::P1)));
+        assert_eq!(game.win_coords(), Some([(0, 0), (0, 1), (0, 2), (0, 3)]));
+        assert_eq!(
+            game.win_line().map(|line| (line.coords, line.direction)),
+            Some((
+                vec![(0, 0), (0, 1), (0, 2), (0, 3), (0, 4)],
+                Direction::Vertical
+            ))
+        );
+        assert_eq!(game.connect_length(), 5);
+        assert_eq!(game.replay().last(), Some(game));
+        Ok(())
     }
 
     #[test]
-    fn multiple_wins() -> Result<(), MoveError> {
-        // _ _ _ _ _ _ _
-        // O O _ _ _ _ O
-        // X X X X X X X
-        // O O X X X O O
-        // O X O X O X O
-        // X O O X O O X
-        test_end_game(
-            "1226716747711226634543355137524",
-            Status::Win(Player::P1),
-            // currently chooses ascending diagonal over other directions, but the implementation is subject to change in the future
-            Some([(0, 0), (1, 1), (2, 2), (3, 3)]),
-        )
+    #[cfg(feature = "std")]
+    fn position_key() -> Result<(), MoveError> {
+        use std::collections::hash_map::DefaultHasher;
+
+        fn hash(game: &Game) -> u64 {
+            let mut hasher = DefaultHasher::new();
+            game.hash(&mut hasher);
+            hasher.finish()
+        }
+
+        let a = Game::from_str("1234567")?;
+        let b = Game::from_str("7254361")?;
+        let c = Game::from_str("2134567")?;
+
+        assert_eq!(a.position_key(), b.position_key());
+        assert_eq!(hash(&a), hash(&b));
+        assert!(a.same_position(&b));
+        assert_ne!(a.position_key(), c.position_key());
+        assert!(!a.same_position(&c));
+
+        // The empty boards have the same key but different dimensions
+        assert!(!Game::new().same_position(&Game::with_dimensions(6, 5)));
+        assert_ne!(
+            Game::new().position_key(),
+            Game::from_str("1")?.position_key()
+        );
+        Ok(())
     }
 
     #[test]
-    fn last_win() -> Result<(), MoveError> {
-        // O O O O X X O
-        // X O X X X O X
-        // O X O O O X O
-        // X O X X X O X
-        // O X O O O X O
-        // X O X X X O X
-        test_end_game(
-            "111112222233333144444255555376666667777754",
-            Status::Win(Player::P2),
-            Some([(0, 5), (1, 5), (2, 5), (3, 5)]),
-        )
+    #[cfg(feature = "svg")]
+    fn to_svg() -> Result<(), MoveError> {
+        let svg = Game::new().to_svg();
+        assert!(svg
+            .starts_with("<svg xmlns=\"http://www.w3.org/2000/svg\" width=\"700\" height=\"600\""));
+        assert!(svg.ends_with("</svg>"));
+        assert_eq!(svg.matches("fill=\"white\"").count(), 42);
+
+        // The first piece is drawn in the bottom row
+        let svg = Game::from_str("41")?.to_svg();
+        assert!(svg.contains("<circle cx=\"350\" cy=\"550\" r=\"40\" fill=\"red\"/>"));
+        assert!(svg.contains("<circle cx=\"50\" cy=\"550\" r=\"40\" fill=\"yellow\"/>"));
+        assert_eq!(svg.matches("fill=\"white\"").count(), 40);
+        Ok(())
     }
 
     #[test]
-    fn draw() -> Result<(), MoveError> {
-        // O O O X O O O
-        // X X X O X X X
-        // O O O X O O O
-        // X X X O X X X
-        // O O O X O O O
-        // X X X O X X X
-        test_end_game(
-            "111111222222333333544444455555666666777777",
-            Status::Draw,
-            None,
-        )
+    #[cfg(feature = "zobrist")]
+    fn zobrist_key() -> Result<(), MoveError> {
+        let moves = "5554224333234511764415115";
+        let mut game = Game::new();
+        let mut keys = vec![game.zobrist_key()];
+
+        for col in moves.bytes().map(|c| c - b'1') {
+            game.play(col)?;
+            // The incremental hash matches the hash computed from the pieces
+            assert_eq!(
+                game.zobrist_key(),
+                Game::from_matrix(game.matrix())?.zobrist_key()
+            );
+            keys.push(game.zobrist_key());
+        }
+
+        while game.undo().is_some() {
+            keys.pop();
+            assert_eq!(Some(&game.zobrist_key()), keys.last());
+        }
+        assert_eq!(game.zobrist_key(), 0);
+
+        let game = Game::from_str("1123")?;
+        let mirror = Game::from_str("7765")?;
+        assert_eq!(game.board.mirror().zobrist(), mirror.zobrist_key());
+        Ok(())
     }
 
     #[test]
-    fn format_string() -> Result<(), MoveError> {
-        // _ _ _ _ _ _ X
-        // _ _ _ _ _ X O
-        // _ _ _ _ O O X
-        // _ _ _ O X X O
-        // _ _ X X O O X
-        // _ X O O X X O
-        let game = Game::from_str("233444555566666777777")?;
-
-        assert_eq!(game.to_string(), "_ _ _ _ _ _ X\n_ _ _ _ _ X O\n_ _ _ _ O O X\n_ _ _ O X X O\n_ _ X X O O X\n_ X O O X X O");
+    fn canonical_key() -> Result<(), MoveError> {
+        let moves = "233444555566666777777";
+        let mirrored: String = moves
+            .chars()
+            .map(|c| char::from(b'8' - c as u8 + b'0'))
+            .collect();
+
+        let game = Game::from_str(moves)?;
+        let mirror = Game::from_str(&mirrored)?;
+        assert_ne!(game.position_key(), mirror.position_key());
+        assert_eq!(game.canonical_key(), mirror.canonical_key());
+        assert!(!game.is_symmetric());
+
+        let game = Game::from_str("441177")?;
+        assert!(game.is_symmetric());
+        assert_eq!(game.canonical_key(), game.position_key());
+
+        let mut game = Game::with_dimensions(8, 7);
+        game.play_slice(&[0, 3, 7, 4])?;
+        assert!(game.is_symmetric());
         Ok(())
     }
 
     #[test]
-    fn undo_moves() -> Result<(), MoveError> {
-        let init_game = Game::new();
-        let mut game = Game::from_str("111112222233333144444255555376666667777754")?;
+    fn perft_detailed() {
+        fn count(game: &mut Game, depth: u8, stats: &mut PerftStats) {
+            if depth == 0 {
+                stats.nodes += 1;
+                match game.status() {
+                    Status::Win(_) => stats.terminal_wins += 1,
+                    Status::Draw => stats.terminal_draws += 1,
+                    Status::Ongoing => (),
+                }
+                return;
+            }
 
-        for i in 0..game.num_moves() {
-            assert_eq!(game.num_moves(), AREA - i);
-            game.undo();
+            for col in 0..WIDTH {
+                if game.play(col).is_ok() {
+                    count(game, depth - 1, stats);
+                    game.undo();
+                }
+            }
         }
 
-        assert_eq!(init_game, game);
-        assert_eq!(game.num_moves(), 0);
-        assert_eq!(game.moves().len(), 0);
-        assert_eq!(game.undo(), None);
-        Ok(())
+        for depth in 0..=8 {
+            let mut expected = PerftStats::default();
+            count(&mut Game::new(), depth, &mut expected);
+            assert_eq!(Game::perft_detailed(depth), expected, "depth = {depth}");
+        }
+
+        assert_eq!(Game::perft_detailed(4).nodes, 7u64.pow(4));
+        assert_eq!(Game::perft_detailed(6).terminal_wins, 0);
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn terminal_stats() {
+        // The positions that end at each depth plus the ongoing positions are the perft counts
+        for (depth, (p1_wins, p2_wins, ongoing)) in [
+            (0, 0, 1),
+            (0, 0, 7),
+            (0, 0, 49),
+            (0, 0, 238),
+            (0, 0, 1120),
+            (0, 0, 4263),
+            (0, 0, 16422),
+            (728, 0, 54131),
+            (728, 1892, 182383),
+        ]
+        .into_iter()
+        .enumerate()
+        {
+            let expected = TerminalStats {
+                p1_wins,
+                p2_wins,
+                draws: 0,
+                ongoing,
+            };
+            assert_eq!(
+                Game::terminal_stats(depth.try_into().unwrap()),
+                expected,
+                "depth = {depth}"
+            );
+        }
     }
 
+    #[cfg(feature = "std")]
     fn test_perft_file<T>(depth: T)
     where
         T: RangeBounds<u8>,
@@ -795,33 +3909,54 @@ mod tests {
                 expected, actual,
                 "perft({i}) expected = {expected}, actual = {actual}"
             );
+
+            let actual = Game::perft_iter(i, None);
+            assert_eq!(
+                expected, actual,
+                "perft_iter({i}) expected = {expected}, actual = {actual}"
+            );
+        }
+    }
+
+    #[test]
+    #[cfg(feature = "std")]
+    fn perft_iter_bounded() {
+        for depth in 0..9 {
+            let expected = Game::perft(depth);
+            assert_eq!(Game::perft_iter(depth, Some(usize::MAX)), expected);
+            assert!(Game::perft_iter(depth, Some(1000)) >= expected);
         }
     }
 
     #[test]
+    #[cfg(feature = "std")]
     fn perft_shallow() {
         test_perft_file(..7);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     #[ignore = "too slow"]
     fn perft_deep() {
         test_perft_file(7..21);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     #[ignore = "too slow"]
     fn perft_deeper() {
         test_perft_file(21..35);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     #[ignore = "too slow"]
     fn perft_deepest() {
         test_perft_file(35..42);
     }
 
     #[test]
+    #[cfg(feature = "std")]
     #[ignore = "too slow"]
     fn perft_max() {
         test_perft_file(42..);
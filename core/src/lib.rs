@@ -1,4 +1,17 @@
 //! A library for playing, solving, and analyzing the game of [Connect Four](https://en.wikipedia.org/wiki/Connect_Four).
+//!
+//! # Features
+//! * `std` (enabled by default): the [`Engine`], the caches, and the perft functions that need the standard library.
+//!   Without it, the crate is `#![no_std]` and only needs the `alloc` crate for playing games with [`Game`].
+//! * `parallel`: `Engine::evaluate_next_parallel`, which also enables `std`.
+//! * `rand`: random moves and puzzle generation.
+//! * `svg`: exporting the board as an SVG image.
+//! * `tt-check`: `Engine::evaluate_checked` for debugging the transposition table, which also enables `std`.
+//! * `zobrist`: incrementally-updated Zobrist hashes of positions, which are not used by the engine.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+
+extern crate alloc;
 
 mod bitboard;
 
@@ -11,10 +24,17 @@ pub use error::*;
 mod game;
 pub use game::*;
 
+mod transcript;
+pub use transcript::*;
+
+#[cfg(feature = "std")]
 mod engine;
+#[cfg(feature = "std")]
 pub use engine::*;
 
+#[cfg(feature = "std")]
 mod cache;
+#[cfg(feature = "std")]
 pub use cache::*;
 
 /// The number of rows in a standard board.
@@ -26,6 +46,9 @@ pub const WIDTH: u8 = 7;
 /// The number of tiles in a standard board.
 pub const AREA: u8 = WIDTH * HEIGHT;
 
+/// The number of pieces in a row needed to win a standard game.
+pub const CONNECT_LENGTH: u8 = 4;
+
 /// Represents a single player.
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum Player {
@@ -33,7 +56,7 @@ pub enum Player {
     P2,
 }
 
-use std::{fmt, ops::Not};
+use core::{fmt, ops::Not};
 
 impl Not for Player {
     type Output = Self;
@@ -0,0 +1,268 @@
+//! Functionality for saving and loading games with metadata in a human-readable format.
+
+use crate::{Game, MoveError, Player, Status};
+use alloc::{string::String, vec::Vec};
+use core::{fmt, str::FromStr};
+
+/// A record of the moves of a game with optional metadata, which can be saved and loaded in a line-based format similar to PGN.
+///
+/// Each piece of metadata is on its own tag line, followed by a line of the 1-indexed columns of the moves:
+/// ```text
+/// [P1 "Alice"]
+/// [P2 "Bob"]
+/// [Date "2024-05-01"]
+/// [Result "1-0"]
+/// 4453
+/// ```
+/// The result is `1-0` if player 1 won, `0-1` if player 2 won, `1/2-1/2` for a draw, or `*` for an ongoing game.
+/// Tags for missing metadata are left out. Backslashes, quotes, and line breaks in metadata are escaped with a backslash,
+/// as `\\`, `\"`, `\n`, and `\r`, so that each tag stays on one line.
+///
+/// # Examples
+/// ```
+/// use connect_four_engine::{Game, GameTranscript, Status};
+///
+/// let mut transcript = Game::from_str("4453")?.transcript().unwrap();
+/// transcript.p1_name = Some("Alice".to_string());
+///
+/// let text = transcript.to_string();
+/// assert_eq!(text, "[P1 \"Alice\"]\n[Result \"*\"]\n4453");
+///
+/// let parsed: GameTranscript = text.parse()?;
+/// assert_eq!(parsed, transcript);
+/// assert_eq!(parsed.game()?.status(), Status::Ongoing);
+/// # Ok::<(), connect_four_engine::MoveError>(())
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct GameTranscript {
+    /// The 0-indexed columns of the moves in the order they were played.
+    pub moves: Vec<u8>,
+    /// The name of the first player.
+    pub p1_name: Option<String>,
+    /// The name of the second player.
+    pub p2_name: Option<String>,
+    /// The date that the game was played, in any format.
+    pub date: Option<String>,
+    /// The result of the game, which can differ from the status of the moves for games ended early, such as by resigning.
+    pub result: Option<Status>,
+}
+
+impl GameTranscript {
+    /// Replays the moves of the transcript from an empty board, returning the game.
+    ///
+    /// # Errors
+    /// Returns a [`MoveError`] if the moves cannot be played.
+    pub fn game(&self) -> Result<Game, MoveError> {
+        let mut game = Game::new();
+        game.play_slice(&self.moves)?;
+        Ok(game)
+    }
+}
+
+/// Returns the result tag value of a game status.
+fn result_str(status: Status) -> &'static str {
+    match status {
+        Status::Win(Player::P1) => "1-0",
+        Status::Win(Player::P2) => "0-1",
+        Status::Draw => "1/2-1/2",
+        Status::Ongoing => "*",
+    }
+}
+
+/// Escapes the backslashes, quotes, and line breaks of a tag value.
+fn escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '\\' => escaped.push_str("\\\\"),
+            '"' => escaped.push_str("\\\""),
+            '\n' => escaped.push_str("\\n"),
+            '\r' => escaped.push_str("\\r"),
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// Reverses [`escape`] on a tag value.
+fn unescape(value: &str) -> Result<String, MoveError> {
+    let mut unescaped = String::with_capacity(value.len());
+    let mut chars = value.chars();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            '\\' => match chars.next() {
+                Some('\\') => '\\',
+                Some('"') => '"',
+                Some('n') => '\n',
+                Some('r') => '\r',
+                _ => return Err(MoveError::InvalidFormat),
+            },
+            '"' => return Err(MoveError::InvalidFormat),
+            c => c,
+        };
+        unescaped.push(c);
+    }
+    Ok(unescaped)
+}
+
+/// Parses a result tag value into a game status.
+fn parse_result(s: &str) -> Result<Status, MoveError> {
+    match s {
+        "1-0" => Ok(Status::Win(Player::P1)),
+        "0-1" => Ok(Status::Win(Player::P2)),
+        "1/2-1/2" => Ok(Status::Draw),
+        "*" => Ok(Status::Ongoing),
+        _ => Err(MoveError::InvalidFormat),
+    }
+}
+
+impl fmt::Display for GameTranscript {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let tags = [
+            ("P1", self.p1_name.as_deref()),
+            ("P2", self.p2_name.as_deref()),
+            ("Date", self.date.as_deref()),
+            ("Result", self.result.map(result_str)),
+        ];
+        for (key, value) in tags {
+            if let Some(value) = value {
+                writeln!(f, "[{key} \"{}\"]", escape(value))?;
+            }
+        }
+
+        for col in &self.moves {
+            write!(f, "{}", col + 1)?;
+        }
+        Ok(())
+    }
+}
+
+impl FromStr for GameTranscript {
+    type Err = MoveError;
+
+    /// Parses a transcript, validating the moves by playing them from an empty board.
+    ///
+    /// # Errors
+    /// Returns [`MoveError::InvalidFormat`] if a tag is malformed or unknown, there is more than one line of moves,
+    /// or the result does not match the status of a finished game. Otherwise, returns a [`MoveError`] if the moves cannot be played.
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut transcript = Self::default();
+        let mut moves = None;
+
+        for line in s.lines().map(str::trim).filter(|line| !line.is_empty()) {
+            let Some(tag) = line.strip_prefix('[') else {
+                if moves.replace(line).is_some() {
+                    return Err(MoveError::InvalidFormat);
+                }
+                continue;
+            };
+
+            let (key, value) = tag
+                .strip_suffix(']')
+                .and_then(|tag| tag.split_once(' '))
+                .ok_or(MoveError::InvalidFormat)?;
+            let value = value
+                .trim()
+                .strip_prefix('"')
+                .and_then(|value| value.strip_suffix('"'))
+                .ok_or(MoveError::InvalidFormat)?;
+            let value = unescape(value)?;
+
+            match key {
+                "P1" => transcript.p1_name = Some(value),
+                "P2" => transcript.p2_name = Some(value),
+                "Date" => transcript.date = Some(value),
+                "Result" => transcript.result = Some(parse_result(&value)?),
+                _ => return Err(MoveError::InvalidFormat),
+            }
+        }
+
+        let game = Game::from_str(moves.unwrap_or_default())?;
+        if let Some(result) = transcript.result {
+            if game.is_over() && result != game.status() {
+                return Err(MoveError::InvalidFormat);
+            }
+        }
+
+        transcript.moves = game.moves().to_vec();
+        Ok(transcript)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use alloc::{string::ToString, vec};
+
+    #[test]
+    fn round_trip() -> Result<(), MoveError> {
+        let transcript = GameTranscript {
+            moves: Game::from_str("4122343344")?.moves().to_vec(),
+            p1_name: Some("Alice".to_string()),
+            p2_name: Some("Bob \"the Builder\"".to_string()),
+            date: Some("2024-05-01".to_string()),
+            result: Some(Status::Win(Player::P2)),
+        };
+
+        let text = transcript.to_string();
+        assert_eq!(
+            text,
+            "[P1 \"Alice\"]\n[P2 \"Bob \\\"the Builder\\\"\"]\n[Date \"2024-05-01\"]\n[Result \"0-1\"]\n4122343344"
+        );
+        assert_eq!(text.parse::<GameTranscript>()?, transcript);
+
+        // Line breaks and tag endings in metadata do not start new lines or end tags
+        let transcript = GameTranscript {
+            p1_name: Some("Alice\n[Result \"0-1\"]".to_string()),
+            p2_name: Some("C:\\Bob\\\"]".to_string()),
+            date: Some("2024\r\n05".to_string()),
+            ..GameTranscript::default()
+        };
+        let text = transcript.to_string();
+        assert_eq!(text.lines().count(), 3);
+        assert_eq!(text.parse::<GameTranscript>()?, transcript);
+
+        let empty = GameTranscript::default();
+        assert_eq!(empty.to_string(), "");
+        assert_eq!("".parse::<GameTranscript>()?, empty);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_lenient_whitespace() -> Result<(), MoveError> {
+        let transcript: GameTranscript = "\n  [Result \"1/2-1/2\"]  \n\n4453\n".parse()?;
+        assert_eq!(transcript.result, Some(Status::Draw));
+        assert_eq!(transcript.moves, vec![3, 3, 4, 2]);
+        Ok(())
+    }
+
+    #[test]
+    fn parse_invalid() {
+        for text in [
+            "[Event \"Club game\"]",
+            "[P1 Alice]",
+            "[P1 \"Alice\"",
+            "[P1 \"Al\"ice\"]",
+            "[P1 \"Alice\\\"]",
+            "[P1 \"Alice\\t\"]",
+            "[Result \"2-0\"]",
+            "44\n53",
+            // The first player won with the moves
+            "[Result \"0-1\"]\n1212121",
+        ] {
+            assert_eq!(
+                text.parse::<GameTranscript>(),
+                Err(MoveError::InvalidFormat),
+                "{text}"
+            );
+        }
+
+        assert_eq!(
+            "4444444".parse::<GameTranscript>(),
+            Err(MoveError::ColumnFull)
+        );
+
+        // A game can end early with a result that its moves have not reached
+        assert!("[Result \"1-0\"]\n4453".parse::<GameTranscript>().is_ok());
+    }
+}
@@ -1,4 +1,5 @@
 use connect_four_engine::{Cache, Engine, Game, Player, Status};
+use std::fmt;
 use wasm_bindgen::prelude::*;
 
 #[wasm_bindgen(start)]
@@ -7,6 +8,177 @@ pub fn main() {
     console_error_panic_hook::set_once();
 }
 
+/// Score of a column that cannot be played because it is full or the game is over.
+const UNPLAYABLE_SCORE: i8 = i8::MIN;
+
+/// The analysis of every column of the current position.
+///
+/// On the JS side, this is an object with the read-only properties:
+/// - `scores`: an `Int8Array` of the 7 column scores from left to right, from the perspective of the player to move,
+///   where an unplayable column has a score of `-128`
+/// - `bestMove`: the 0-indexed column with the highest score, or `255` if no column is playable
+/// - `positionEval`: the [`PositionEval`] label of the position
+/// - `scoreSpread`: the [`ScoreSpread`] of the playable columns, which shows how sharp the position is
+///
+/// Analyses from `analyzeDeep` also have the expected continuation after each column, returned by `lineAfter`.
+#[wasm_bindgen]
+pub struct Analysis {
+    scores: Vec<i8>,
+    best_move: u8,
+    /// Whether every score is exact, which is false if a search was aborted before solving its position.
+    exact: bool,
+    /// The principal variation after each column, or empty if the analysis is not deep.
+    lines: Vec<Option<Vec<u8>>>,
+}
+
+#[wasm_bindgen]
+impl Analysis {
+    #[wasm_bindgen(getter)]
+    pub fn scores(&self) -> Vec<i8> {
+        self.scores.clone()
+    }
+
+    #[wasm_bindgen(getter, js_name = bestMove)]
+    pub fn best_move(&self) -> u8 {
+        self.best_move
+    }
+
+    #[wasm_bindgen(getter, js_name = scoreSpread)]
+    pub fn spread(&self) -> ScoreSpread {
+        let (min, max, std_dev) = self.score_spread();
+        ScoreSpread { min, max, std_dev }
+    }
+
+    #[wasm_bindgen(getter, js_name = positionEval)]
+    pub fn position_eval(&self) -> PositionEval {
+        match self.best_score() {
+            Some(score) if self.exact && score > 0 => PositionEval::Winning,
+            Some(score) if self.exact && score < 0 => PositionEval::Losing,
+            Some(0) if self.exact => PositionEval::Drawn,
+            _ => PositionEval::Unclear,
+        }
+    }
+
+    /// Returns the 0-indexed columns expected to be played by both players after playing `col` until the game ends,
+    /// or `undefined` if the column is not playable or the analysis is not from `analyzeDeep`.
+    #[wasm_bindgen(js_name = lineAfter)]
+    pub fn line_after(&self, col: u8) -> Option<Vec<u8>> {
+        self.lines.get(usize::from(col))?.clone()
+    }
+}
+
+impl Analysis {
+    /// Returns the score of the best move, or [`None`] if no column is playable.
+    fn best_score(&self) -> Option<i8> {
+        self.scores.get(usize::from(self.best_move)).copied()
+    }
+
+    /// Returns the minimum and maximum scores of the playable columns along with the standard deviation of their scores,
+    /// which shows how sharp the position is. A wide spread means that the choice of move matters a lot,
+    /// and a narrow spread means that every move leads to a similar result.
+    ///
+    /// If only one column is playable, then both scores are its score and the deviation is 0.
+    /// If no column is playable, then both scores are `-128` like the unplayable scores.
+    pub fn score_spread(&self) -> (i8, i8, f32) {
+        let playable: Vec<i8> = self
+            .scores
+            .iter()
+            .copied()
+            .filter(|&score| score != UNPLAYABLE_SCORE)
+            .collect();
+
+        let (Some(&min), Some(&max)) = (playable.iter().min(), playable.iter().max()) else {
+            return (UNPLAYABLE_SCORE, UNPLAYABLE_SCORE, 0.0);
+        };
+
+        let count = playable.len() as f32;
+        let mean = playable.iter().map(|&score| f32::from(score)).sum::<f32>() / count;
+        let variance = playable
+            .iter()
+            .map(|&score| (f32::from(score) - mean).powi(2))
+            .sum::<f32>()
+            / count;
+        (min, max, variance.sqrt())
+    }
+}
+
+/// Formats the analysis as a plain text table of the score and result of each column, followed by the best move.
+///
+/// Unplayable columns are shown as `-`, and results are `W`, `D`, or `L` for the player to move, or `?` if the scores are not exact.
+///
+/// ```text
+/// Column |   1   2   3   4   5   6   7
+/// Score  |  -2  -1   0   3   -  -1  -2
+/// Result |   L   L   D   W   -   L   L
+/// Best move: 4 (Winning)
+/// ```
+impl fmt::Display for Analysis {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let cols: String = (1..=self.scores.len())
+            .map(|col| format!("{col:>4}"))
+            .collect();
+        writeln!(f, "Column |{cols}")?;
+
+        let scores: String = self
+            .scores
+            .iter()
+            .map(|&score| match score {
+                UNPLAYABLE_SCORE => format!("{:>4}", "-"),
+                score => format!("{score:>4}"),
+            })
+            .collect();
+        writeln!(f, "Score  |{scores}")?;
+
+        let results: String = self
+            .scores
+            .iter()
+            .map(|&score| {
+                let result = match score {
+                    UNPLAYABLE_SCORE => '-',
+                    _ if !self.exact => '?',
+                    1.. => 'W',
+                    0 => 'D',
+                    _ => 'L',
+                };
+                format!("{result:>4}")
+            })
+            .collect();
+        writeln!(f, "Result |{results}")?;
+
+        if self.best_score().is_some() {
+            write!(
+                f,
+                "Best move: {} ({:?})",
+                self.best_move + 1,
+                self.position_eval()
+            )
+        } else {
+            write!(f, "Best move: none")
+        }
+    }
+}
+
+/// The minimum and maximum scores of the playable columns and the standard deviation of their scores, as returned by [`Analysis::score_spread`].
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct ScoreSpread {
+    pub min: i8,
+    pub max: i8,
+    #[wasm_bindgen(js_name = stdDev)]
+    pub std_dev: f32,
+}
+
+/// A label of the current position for the player to move, with perfect play from both players.
+#[wasm_bindgen]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PositionEval {
+    Winning,
+    Losing,
+    Drawn,
+    /// No column is playable, or the scores are not exact.
+    Unclear,
+}
+
 #[wasm_bindgen]
 pub struct App {
     engine: Engine,
@@ -54,4 +226,202 @@ impl App {
     pub fn evaluate(&mut self) -> i8 {
         self.engine.evaluate(&self.game)
     }
+
+    /// Analyzes every column of the current position.
+    ///
+    /// The engine keeps its transposition table between calls, so analyzing the position after a move
+    /// reuses the scores found while analyzing the previous position.
+    pub fn analyze(&mut self) -> Analysis {
+        if self.game.is_over() {
+            return Analysis {
+                scores: vec![UNPLAYABLE_SCORE; 7],
+                best_move: u8::MAX,
+                exact: true,
+                lines: Vec::new(),
+            };
+        }
+
+        let scores = self
+            .engine
+            .evaluate_next(&self.game)
+            .map(|score| score.unwrap_or(UNPLAYABLE_SCORE));
+
+        let best_move = (0..)
+            .zip(scores)
+            .filter(|&(_, score)| score != UNPLAYABLE_SCORE)
+            .max_by_key(|&(_, score)| score)
+            .map_or(u8::MAX, |(col, _)| col);
+
+        Analysis {
+            scores: scores.to_vec(),
+            best_move,
+            exact: self.engine.last_eval_exact(),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Analyzes the current position like [`analyze`](App::analyze), and also finds the principal variation after each playable column,
+    /// so that [`Analysis::line_after`] does not need another search.
+    ///
+    /// Finding every line searches many more positions, so this is much slower than `analyze`.
+    #[wasm_bindgen(js_name = analyzeDeep)]
+    pub fn analyze_deep(&mut self) -> Analysis {
+        let mut analysis = self.analyze();
+        analysis.lines = (0..)
+            .zip(&analysis.scores)
+            .map(|(col, &score)| {
+                if score == UNPLAYABLE_SCORE {
+                    return None;
+                }
+
+                let mut game = self.game.clone();
+                game.play(col).ok()?;
+                Some(self.engine.principal_variation(&game))
+            })
+            .collect();
+        analysis
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::cmp::Ordering;
+
+    fn analysis(scores: [i8; 7], best_move: u8) -> Analysis {
+        Analysis {
+            scores: scores.to_vec(),
+            best_move,
+            exact: true,
+            lines: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn analyze_finished() {
+        let mut app = App::new(Cache::default().to_bytes());
+        for col in [0, 1, 0, 1, 0, 1, 0] {
+            app.play(col);
+        }
+        assert!(app.is_game_over());
+
+        let analysis = app.analyze();
+        assert_eq!(analysis.scores(), vec![UNPLAYABLE_SCORE; 7]);
+        assert_eq!(analysis.best_move(), u8::MAX);
+        assert_eq!(analysis.position_eval(), PositionEval::Unclear);
+        assert_eq!(app.analyze_deep().line_after(0), None);
+    }
+
+    #[test]
+    fn position_eval() {
+        let mut app = App::new(Cache::default().to_bytes());
+        for col in [3, 2, 1, 6, 4, 6, 2, 5] {
+            app.play(col - 1);
+        }
+        assert_eq!(app.analyze().position_eval(), PositionEval::Winning);
+
+        const X: i8 = UNPLAYABLE_SCORE;
+        assert_eq!(
+            analysis([-4, -3, X, -2, X, -5, -1], 6).position_eval(),
+            PositionEval::Losing
+        );
+        assert_eq!(
+            analysis([0, -3, X, X, X, X, X], 0).position_eval(),
+            PositionEval::Drawn
+        );
+        assert_eq!(
+            analysis([X; 7], u8::MAX).position_eval(),
+            PositionEval::Unclear
+        );
+
+        let mut inexact = analysis([1, 0, 0, 0, 0, 0, 0], 0);
+        inexact.exact = false;
+        assert_eq!(inexact.position_eval(), PositionEval::Unclear);
+    }
+
+    #[test]
+    fn display() {
+        const X: i8 = UNPLAYABLE_SCORE;
+        assert_eq!(
+            analysis([-2, -1, 0, 3, X, -1, -2], 3).to_string(),
+            "Column |   1   2   3   4   5   6   7\n\
+             Score  |  -2  -1   0   3   -  -1  -2\n\
+             Result |   L   L   D   W   -   L   L\n\
+             Best move: 4 (Winning)"
+        );
+
+        let mut inexact = analysis([X, X, X, 0, X, X, -12], 3);
+        inexact.exact = false;
+        assert_eq!(
+            inexact.to_string(),
+            "Column |   1   2   3   4   5   6   7\n\
+             Score  |   -   -   -   0   -   - -12\n\
+             Result |   -   -   -   ?   -   -   ?\n\
+             Best move: 4 (Unclear)"
+        );
+
+        assert!(analysis([X; 7], u8::MAX)
+            .to_string()
+            .ends_with("Result |   -   -   -   -   -   -   -\nBest move: none"));
+    }
+
+    #[test]
+    fn score_spread() {
+        const X: i8 = UNPLAYABLE_SCORE;
+        assert_eq!(
+            analysis([-2, X, 2, 0, X, X, X], 2).score_spread(),
+            (-2, 2, (8.0f32 / 3.0).sqrt())
+        );
+
+        // Only one column is playable
+        let one_move = analysis([X, X, X, 5, X, X, X], 3);
+        assert_eq!(one_move.score_spread(), (5, 5, 0.0));
+        assert_eq!(
+            one_move.spread(),
+            ScoreSpread {
+                min: 5,
+                max: 5,
+                std_dev: 0.0
+            }
+        );
+
+        // No column is playable
+        let no_moves = analysis([X; 7], u8::MAX);
+        assert_eq!(no_moves.score_spread(), (X, X, 0.0));
+        assert_eq!(no_moves.spread().min, X);
+    }
+
+    #[test]
+    fn analyze_deep() {
+        let mut app = App::new(Cache::default().to_bytes());
+        for col in "5554224333234511764415115".bytes() {
+            app.play(col - b'1');
+        }
+        let player = app.game.turn();
+        let analysis = app.analyze_deep();
+
+        // The fifth column is full
+        assert_eq!(analysis.scores[4], UNPLAYABLE_SCORE);
+        assert_eq!(analysis.line_after(4), None);
+        assert_eq!(analysis.line_after(7), None);
+
+        for (col, &score) in (0..).zip(&analysis.scores) {
+            if score == UNPLAYABLE_SCORE {
+                continue;
+            }
+
+            let mut game = app.game.clone();
+            game.play(col).unwrap();
+            game.play_slice(&analysis.line_after(col).unwrap()).unwrap();
+
+            let expected = match score.cmp(&0) {
+                Ordering::Greater => Status::Win(player),
+                Ordering::Less => Status::Win(!player),
+                Ordering::Equal => Status::Draw,
+            };
+            assert_eq!(game.status(), expected, "col = {col}");
+        }
+
+        assert_eq!(app.analyze().line_after(0), None);
+    }
 }